@@ -0,0 +1,93 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Sx1255Error;
+use crate::freq::Reference;
+use crate::info::SX1255Info;
+
+/// The 70 cm amateur band, 420 to 450 MHz
+pub static AMATEUR_70CM: TxBand = TxBand { low: 420000000, high: 450000000 };
+
+/// A range of Tx frequencies in Hz, both ends included
+///
+/// In a hardware profile it is written as a `[low, high]` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "(u32, u32)", into = "(u32, u32)")]
+pub struct TxBand {
+    pub low: u32,
+    pub high: u32,
+}
+
+impl TxBand {
+    /// Whether `freq` Hz is inside the band
+    pub fn contains(&self, freq: f64) -> bool {
+        (self.low as f64..=self.high as f64).contains(&freq)
+    }
+}
+
+impl From<(u32, u32)> for TxBand {
+    fn from((low, high): (u32, u32)) -> TxBand {
+        TxBand { low, high }
+    }
+}
+
+impl From<TxBand> for (u32, u32) {
+    fn from(band: TxBand) -> (u32, u32) {
+        (band.low, band.high)
+    }
+}
+
+impl fmt::Display for TxBand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{} MHz", self.low as f64 / 1e6, self.high as f64 / 1e6)
+    }
+}
+
+/// Checks the PA driver is only enabled inside one of `bands`
+///
+/// What counts is the frequency the synthesizer produces for `tx_freq`,
+/// after rounding to a PLL step and the reference correction, so a request
+/// right at a band edge can't land just outside it.
+pub fn check_tx(bands: &[TxBand], sx1255_info: &SX1255Info, reference: &Reference) -> Result<(), Sx1255Error> {
+    if !sx1255_info.driver_enable {
+        return Ok(());
+    }
+    let actual = reference.tune(sx1255_info.tx_freq)?.actual;
+    if bands.iter().any(|band| band.contains(actual)) {
+        return Ok(());
+    }
+    let allowed = match bands {
+        [] => "no Tx bands are allowed".to_string(),
+        _ => format!("allowed: {}", bands.iter().map(|band| band.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    Err(Sx1255Error::Interlock(format!("Tx at {:.0} Hz is out of band ({})", actual, allowed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_keys_in_band() {
+        let bands = [AMATEUR_70CM];
+        let reference = Reference::default();
+        let mut sx1255_info = SX1255Info { tx_freq: 460000000, ..SX1255Info::default() };
+        // out of band is fine as long as the PA driver is off
+        check_tx(&bands, &sx1255_info, &reference).unwrap();
+        sx1255_info.driver_enable = true;
+        let e = check_tx(&bands, &sx1255_info, &reference).unwrap_err();
+        assert_eq!(e.to_string(), "Tx interlock: Tx at 460000000 Hz is out of band (allowed: 420-450 MHz)");
+
+        for (tx_freq, allowed) in [(420000000, true), (450000000, true), (450000020, false)] {
+            sx1255_info.tx_freq = tx_freq;
+            assert_eq!(check_tx(&bands, &sx1255_info, &reference).is_ok(), allowed, "{}", tx_freq);
+        }
+        assert!(check_tx(&[], &sx1255_info, &reference).is_err());
+
+        // with a 10 Hz correction the nearest step to 450 MHz lands above it
+        let corrected = Reference::default().with_correction(0.0, 10);
+        sx1255_info.tx_freq = 450000000;
+        assert!(corrected.tune(sx1255_info.tx_freq).unwrap().actual > 450000000.0);
+        assert!(check_tx(&bands, &sx1255_info, &corrected).is_err());
+    }
+}
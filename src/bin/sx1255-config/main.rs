@@ -1,30 +1,44 @@
-use std::{io};
-use clap::{Parser, Subcommand};
-use spidev::{Spidev, SpidevOptions, SpiModeFlags};
-use std::path::PathBuf;
-use gpio_cdev::{Chip, LineRequestFlags};
-
-use crate::info::{SX1255Info, get_info, print_info, set_info};
-use crate::file::{write_file, read_file};
-use crate::opts::OPTS;
-
-pub mod info;
-pub mod file;
-pub mod opts;
-
-static SPI_DEV: &str = "/dev/spidev0.0";
-static SPI_OPTS: SpidevOptions = SpidevOptions {
-    bits_per_word: Some(8),
-    max_speed_hz: Some(500000),
-    lsb_first: Some(false),
-    spi_mode: Some(SpiModeFlags::SPI_MODE_0),
-};
+use clap::{Arg, ArgMatches, Command, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap::error::ErrorKind;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitCode};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use chrono::{Local, SecondsFormat};
+
+use sx1255::{HwConfig, RegisterBus, Sx1255, Sx1255Error, reset};
+use sx1255::fields::{self, Field, FIELDS};
+use sx1255::hw::HwArgs;
+use sx1255::info::{SX1255Info, compare_info, print_dump, print_info};
+use sx1255::regs::{NUM_REGS, REG_NAMES};
+use sx1255::channel::{ChannelTable, default_channels_file};
+use sx1255::duplex::{Duplex, Shift, user_duplex_file};
+use sx1255::freq::{Reference, Tuning};
+use sx1255::profile::ProfileStore;
+use sx1255::rate::{Rates, plan};
+use sx1255::report::Report;
+use sx1255::status::{self, Event};
+use sx1255::file::{write_file, read_file, merge_file};
 
 #[derive(Parser)]
 #[command(name = "sx1255-config")]
 #[command(version)]
 #[command(about = "Configure the M17 sx1255 HAT via SPI/GPIO")]
 struct Cli {
+    #[command(flatten)]
+    hw: HwArgs,
+
+    /// allow the PA driver on outside the tx_bands of the hardware profile
+    #[arg(long, global = true)]
+    override_tx_bands: bool,
+
+    /// duplex setting [default: ~/.config/sx1255/duplex.toml]
+    #[arg(long, global = true, env = "SX1255_DUPLEX_FILE")]
+    duplex_file: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -32,7 +46,11 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Prints info about device state
-    Info,
+    Info {
+        /// output format
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
     /// Resets the device
     Reset,
     /// Save device state to file
@@ -46,424 +64,1442 @@ enum Commands {
         /// file name
         #[arg()]
         file: PathBuf,
+        /// only change the settings in the file, keeping the rest of the device state
+        #[arg(long)]
+        merge: bool,
+        /// wait this many milliseconds for the enabled PLLs to lock
+        #[arg(long, value_name = "MS")]
+        wait_lock: Option<u64>,
     },
-    /// Sets a register variable
+    /// Sets one or more register variables
+    #[command(arg_required_else_help = true)]
     Set {
+        /// wait this many milliseconds for the enabled PLLs to lock
+        #[arg(long, value_name = "MS", global = true)]
+        wait_lock: Option<u64>,
+        /// settings to write together in a single update, e.g. rx_freq=435M rx_enable=true
+        #[arg(value_name = "NAME=VALUE", value_parser=assignment)]
+        assignments: Vec<(&'static Field, u32)>,
         /// register variable name
         #[command(subcommand)]
-        name: SetCommands,
-    },
-}
-
-#[derive(Subcommand)]
-#[command(rename_all = "snake_case")]
-enum SetCommands {
-    /// Enables the PA driver
-    DriverEnable{
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
-    },
-    /// Enables the complete Tx part of the front-end (except the PA)
-    TxEnable {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
-    },
-    /// Enables the complete Rx part of the front-end
-    RxEnable {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
-    },
-    /// Enables the PDS and the oscillator
-    RefEnable {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
-    },
-    /// Sets the Rx frequency
-    RxFreq {
-        #[arg(value_parser=clap::value_parser!(u32).range(300000000..500000000))]
-        freq: u32,
-    },
-    /// Sets the Tx frequency
-    TxFreq {
-        #[arg(value_parser=clap::value_parser!(u32).range(300000000..500000000))]
-        freq: u32,
-    },
-    /// Sets the Tx DAC gain 
-    TxDacGain {
-        /// 0: max gain - 9 dB
-        /// 1: max gain - 6 dB
-        /// 2: max gain - 3 dB
-        /// 3: max gain - 0 dB
-        /// test modes not recommended
-        /// 4: max gain - 9 dB with test Vref voltage
-        /// 5: max gain - 6 dB with test Vref voltage
-        /// 6: max gain - 3 dB with test Vref voltage
-        /// 7: max gain - 0 dB with test Vref voltage
-        #[arg(verbatim_doc_comment, value_parser=clap::value_parser!(u8).range(0..8))]
-        gain: u8,
-    },
-    /// Sets the Tx mixer gain
-    TxMixerGain {
-        /// Gain ~ -37.5 + 2 x GAIN in dB (GAIN must be between 0-15)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..16))]
-        gain: u8,
-    },
-    /// Sets the capacitance in parallel with the mixer tank
-    TxMixerTankCap {
-        /// Capacitance = 128 * CAP fF (CAP must be between 0-7)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..8))]
-        cap: u8,
-    },
-    /// Sets the resistance inparallel with the mixer tank
-    TxMixerTankRes {
-        /// 0: 0.95 kΩ
-        /// 1: 1.11 kΩ
-        /// 2: 1.32 kΩ
-        /// 3: 1.65 kΩ
-        /// 4: 2.18 kΩ
-        /// 5: 3.24 kΩ
-        /// 6: 6.00 kΩ
-        /// 7: none => about 64 kΩ
-        #[arg(value_parser=clap::value_parser!(u8).range(0..8))]
-        res: u8,
-    },
-    /// Sets Tx PLL bandwidth
-    TxPllBw {
-        /// PLL Bandwidth = (BW + 1) * 75 KHz (BW must be between 0-3)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        bw: u8,
-    },
-    /// Sets Tx analog filter bandwidth DSB
-    TxFilterBw {
-        /// BW_3dB = 17.15 / (41 - BW) MHz (BW must be between 0-16)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..16))]
-        bw: u8,
+        name: Option<SetCommand>,
     },
-    /// Sets the number of taps for the Tx FIR-DAC
-    TxDacBw {
-        /// Actual number of taps = 24 + 8 * BW, max = 64
-        #[arg(value_parser=clap::value_parser!(u8).range(0..8))]
-        bw: u8,
+    /// Shows the IISM clocks and I/Q sample rate, or plans them for a target rate
+    Rate {
+        /// find the IISM mode, divider and r closest to this I/Q sample rate in Hz
+        #[arg(short, long)]
+        target: Option<u32>,
+        /// write the planned configuration to the device
+        #[arg(long, requires = "target")]
+        apply: bool,
     },
-    /// Sets the Rx LNA gain
-    RxLnaGain {
-        /// 0 = not used
-        /// 1 = G1 - highest gain power - 0 dB
-        /// 2 = G2 - highest gain power - 6 dB
-        /// 3 = G3 - highest gain power - 12 dB
-        /// 4 = G4 - highest gain power - 24 dB
-        /// 5 = G5 - highest gain power - 36 dB
-        /// 6 = G6 - highest gain power - 48 dB
-        /// 7 = not used
-        #[arg(verbatim_doc_comment, value_parser=clap::value_parser!(u8).range(0..8))]
-        gain: u8,
-    },
-    /// Sets the Rx PGA gain
-    RxPgaGain {
-        /// lowest gain + 2 dB * GAIN (GAIN must be between 0-15)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..16))]
-        gain: u8,
-    },
-    /// Sets the Rx input impedance
-    RxZin200 {
-        /// 0=50Ω, 1=200Ω
-        #[arg(value_parser=clap::value_parser!(u8).range(0..2))]
-        imp: u8,
-    },
-    /// Sets the Rx ADC trim for 36 MHz reference crystal (must be between 0-7)
-    RxAdcTrim {
-        #[arg(value_parser=clap::value_parser!(u8).range(0..8))]
-        trim: u8,
+    /// Compares the device state with a file, or two files
+    Diff {
+        /// file to compare the device state with
+        #[arg()]
+        file: PathBuf,
+        /// compare with this file instead of the device state
+        #[arg()]
+        other: Option<PathBuf>,
     },
-    /// Sets the Rx analog roofing filter
-    RxPgaBw {
-        /// 0=1500 kHz, 1=1000 kHz, 2=750 kHz, 3=500 kHz
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        bw: u8,
+    /// Watches the PLL lock, XOSC, EOL and IISM status flags
+    Watch {
+        /// polling interval in milliseconds
+        #[arg(short, long, default_value_t = 1000)]
+        interval: u64,
+        /// stop after this many polls
+        #[arg(short = 'n', long)]
+        count: Option<u64>,
+        /// print events as JSON lines
+        #[arg(long)]
+        json: bool,
+        /// run a shell command when an event happens, e.g. tx_unlock='logger PLL lost'
+        #[arg(long, value_name = "EVENT=COMMAND", value_parser=hook)]
+        hook: Vec<(String, String)>,
     },
-    /// Sets the Rx PLL bandwidth
-    RxPllBw {
-        /// PLL BW = (BW + 1) * 75 KHz (BW must be between 0-3)
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        bw: u8,
+    /// Keys or unkeys the transmitter
+    Ptt {
+        #[command(subcommand)]
+        command: PttCommands,
     },
-    /// Puts the Rx ADC into temperature measurement mode
-    RxAdcTemp {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
+    /// Shows or changes how the Tx frequency follows the Rx frequency
+    Duplex {
+        #[command(subcommand)]
+        command: Option<DuplexCommands>,
     },
-    /// Mapping of DIO(0)
-    IoMap0 {
-        /// 0=pll_lock_rx, 1=pll_lock_rx, 2=pll_lock_rx, 3=eol
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        value: u8,
+    /// Tunes to a channel from the channel table
+    Tune {
+        /// channel name
+        name: String,
+        /// channel table [default: ~/.config/sx1255/channels.toml or /etc/sx1255/channels.toml]
+        #[arg(long, env = "SX1255_CHANNELS")]
+        channels: Option<PathBuf>,
+        /// wait this many milliseconds for the enabled PLLs to lock
+        #[arg(long, value_name = "MS")]
+        wait_lock: Option<u64>,
     },
-    /// Mapping of DIO(1)
-    IoMap1 {
-        /// 0=pll_lock_rx
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        value: u8,
+    /// Shows the channel table
+    Channels {
+        /// channel table [default: ~/.config/sx1255/channels.toml or /etc/sx1255/channels.toml]
+        #[arg(long, global = true, env = "SX1255_CHANNELS")]
+        channels: Option<PathBuf>,
+        #[command(subcommand)]
+        command: ChannelCommands,
     },
-    /// Mapping of DIO(2)
-    IoMap2 {
-        /// 0=xosc_ready
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        value: u8,
+    /// Manages named configuration profiles
+    Profile {
+        /// profile directory to use instead of ~/.config/sx1255/profiles and /etc/sx1255/profiles
+        #[arg(long, global = true, env = "SX1255_PROFILE_DIR")]
+        profile_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: ProfileCommands,
     },
-    /// Mapping of DIO(3)
-    IoMap3 {
-        /// 0=pll_lock rx in Rx mode & pll_lock_tx in all other modes
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        value: u8,
+    /// Reads and writes registers directly
+    Reg {
+        #[command(subcommand)]
+        command: RegCommands,
     },
-    /// Enables the digital loop back mode of the frontend
-    DigLoopbackEn {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
+}
+
+#[derive(Subcommand)]
+enum PttCommands {
+    /// Enables Tx, waits for the Tx PLL to lock, then turns on the PA driver
+    On {
+        /// stay running and drop the transmission after this many seconds, or when interrupted
+        #[arg(long, value_name = "SECONDS", value_parser = seconds)]
+        timeout: Option<Duration>,
+        /// how long to wait for the Tx PLL to lock in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 100)]
+        wait_lock: u64,
     },
-    /// Enables the RF loop back mode of the frontend
-    RfLoopbackEn {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
+    /// Turns off the PA driver, then Tx
+    Off,
+}
+
+#[derive(Subcommand)]
+enum DuplexCommands {
+    /// Tunes Tx on its own
+    Off,
+    /// Transmits on the Rx frequency
+    Simplex,
+    /// Transmits above the Rx frequency
+    Plus {
+        /// offset in Hz, e.g. 5M
+        #[arg(value_parser = offset)]
+        offset: u32,
     },
-    /// Disable IISM Rx (during Tx mode)
-    IismRxDisable {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
+    /// Transmits below the Rx frequency
+    Minus {
+        /// offset in Hz, e.g. 7.6M
+        #[arg(value_parser = offset)]
+        offset: u32,
     },
-    /// Disable IISM Tx (during Rx mode)
-    IismTxDisable {
-        #[arg(action=clap::ArgAction::Set)]
-        value: bool,
+    /// Swaps Rx and Tx of a plus or minus split
+    Reverse,
+}
+
+#[derive(Subcommand)]
+enum ChannelCommands {
+    /// Lists the channels and their frequencies
+    List,
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// Lists the saved profiles
+    List,
+    /// Prints a profile
+    Show {
+        /// profile name
+        name: String,
     },
-    /// Sets the IISM mode
-    IismMode {
-        /// 0=A, 1=B1, 2=B2, 3=not used
-        #[arg(value_parser=clap::value_parser!(u8).range(0..4))]
-        mode: u8,
+    /// Saves the device state as a profile
+    Save {
+        /// profile name
+        name: String,
+        /// replace an existing profile
+        #[arg(short, long)]
+        force: bool,
     },
-    /// Sets the XTAL/CLK_OUT division factor
-    IismClkDiv {
-        /// 0=1, 1=2, 2=4, 3=8, 4=12, 5=16, 6=24, 7=32, 8=48, higher values not used
-        #[arg(value_parser=clap::value_parser!(u8).range(0..16))]
-        factor: u8,
+    /// Loads a profile onto the device
+    Apply {
+        /// profile name
+        name: String,
+        /// only change the settings in the profile, keeping the rest of the device state
+        #[arg(long)]
+        merge: bool,
+        /// wait this many milliseconds for the enabled PLLs to lock
+        #[arg(long, value_name = "MS")]
+        wait_lock: Option<u64>,
     },
-    /// Sets the interpolation/decimation factor
-    R {
-        /// 8, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1536
-        /// 9, 18, 27, 36, 54, 72, 108, 144, 216, 288, 432, 576, 864, 1728
-        #[arg(verbatim_doc_comment, value_parser=["8", "16", "24", "32", "48",
-              "64", "96", "128", "192", "256", "384", "512", "768", "1536",
-              "9", "18", "27", "36", "54", "72", "108", "144", "216", "288",
-              "432", "576", "864", "1728"])]
-        r_str: String,
+}
+
+#[derive(Subcommand)]
+enum RegCommands {
+    /// Reads one or more consecutive registers
+    Read {
+        /// register address, e.g. 0x0C
+        #[arg(value_parser=reg_addr)]
+        addr: u8,
+        /// number of registers to read in one burst
+        #[arg(default_value_t = 1, value_parser=byte)]
+        count: u8,
     },
-    /// Sets the IISM truncation mode
-    IismTruncation {
-        /// 0=MSB is truncated, alignment of LSB, 1=LSB is truncated, alignment on MSB
-        #[arg(value_parser=clap::value_parser!(u8).range(0..2))]
-        mode: u8,
+    /// Writes one or more consecutive registers
+    Write {
+        /// register address, e.g. 0x0C
+        #[arg(value_parser=reg_addr)]
+        addr: u8,
+        /// values to write, more than one is written as a burst
+        #[arg(required = true, value_parser=byte)]
+        values: Vec<u8>,
     },
+    /// Dumps every register with its fields decoded
+    Dump,
 }
 
-fn reset() -> Result<(), gpio_cdev::Error> {
-    let mut chip = Chip::new("/dev/gpiochip0")?;
-    let output = chip.get_line(25)?;
-    let output_handle = output.request(LineRequestFlags::OUTPUT, 0, "sx1255-config")?;
-    output_handle.set_value(1)?;
-    output_handle.set_value(0)?;
-    Ok(())
+// parses a decimal or 0x prefixed hex byte
+fn byte(s: &str) -> Result<u8, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse(),
+    }.map_err(|e| e.to_string())
+}
+
+// parses a register address
+fn reg_addr(s: &str) -> Result<u8, String> {
+    let addr = byte(s)?;
+    if addr as usize >= NUM_REGS {
+        return Err(format!("registers go from 0x00-0x{:02X}", NUM_REGS - 1));
+    }
+    Ok(addr)
+}
+
+// parses an EVENT=COMMAND watch hook
+fn hook(s: &str) -> Result<(String, String), String> {
+    let (event, command) = s.split_once('=').ok_or("expected EVENT=COMMAND")?;
+    if !status::event_names().any(|name| name == event) {
+        let names: Vec<_> = status::event_names().collect();
+        return Err(format!("unknown event {}, expected one of {}", event, names.join(", ")));
+    }
+    Ok((event.to_string(), command.to_string()))
+}
+
+// parses a positive number of seconds, fractions allowed
+fn seconds(s: &str) -> Result<Duration, String> {
+    let seconds: f64 = s.parse().map_err(|_| format!("{} is not a number of seconds", s))?;
+    match Duration::try_from_secs_f64(seconds) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(format!("{} is out of range", s)),
+    }
+}
+
+// parses a positive offset in Hz, plain or with an SI prefix like 7.6M,
+// rounded to whole Hz
+fn offset(s: &str) -> Result<u32, String> {
+    let offset = match s.parse::<u32>() {
+        Ok(offset) => offset as f64,
+        Err(_) => fields::parse_si(s, "Hz").ok_or_else(|| format!("{} is not a frequency", s))?,
+    };
+    if !(1.0..=u32::MAX as f64).contains(&offset) {
+        return Err(format!("{} is out of range", s));
+    }
+    Ok(offset.round() as u32)
+}
+
+// parses a NAME=VALUE setting for a batch set
+fn assignment(s: &str) -> Result<(&'static Field, u32), String> {
+    let (name, value) = s.split_once('=').ok_or("expected NAME=VALUE")?;
+    let field = fields::find(name)
+        .filter(|field| field.writable)
+        .ok_or_else(|| format!("unknown setting {}", name))?;
+    let value = field.parse(value).map_err(|e| e.to_string())?;
+    Ok((field, value))
+}
+
+/// How `info` prints the device state
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// human readable report
+    Text,
+    Json,
+    Toml,
+    Yaml,
+}
+
+// serializes the device state for scripts
+fn format_report(sx1255_info: &SX1255Info, reference: &Reference, format: Format) -> Result<String, String> {
+    let report = Report::new(sx1255_info, reference);
+    match format {
+        Format::Json => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
+        Format::Toml => toml::to_string(&report).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::to_string(&report).map_err(|e| e.to_string()),
+        Format::Text => unreachable!("text isn't serialized"),
+    }
+}
+
+/// A `set` subcommand, one is generated for every writable field
+struct SetCommand {
+    field: &'static Field,
+    value: u32,
 }
 
-fn create_spi() -> io::Result<Spidev> {
-    let mut spi = Spidev::open(SPI_DEV)?;
-    spi.configure(&SPI_OPTS)?;
-    Ok(spi)
+impl FromArgMatches for SetCommand {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<SetCommand, clap::Error> {
+        let (name, sub_matches) = matches.subcommand()
+            .ok_or_else(|| clap::Error::new(ErrorKind::MissingSubcommand))?;
+        let field = fields::find(name)
+            .ok_or_else(|| clap::Error::new(ErrorKind::InvalidSubcommand))?;
+        let value = *sub_matches.get_one::<u32>("value")
+            .ok_or_else(|| clap::Error::new(ErrorKind::MissingRequiredArgument))?;
+        Ok(SetCommand { field, value })
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        *self = SetCommand::from_arg_matches(matches)?;
+        Ok(())
+    }
+}
+
+impl Subcommand for SetCommand {
+    fn augment_subcommands(cmd: Command) -> Command {
+        FIELDS.iter().filter(|f| f.writable).fold(cmd, |cmd, field| {
+            let value = Arg::new("value")
+                .required(true)
+                .value_name("VALUE")
+                .allow_hyphen_values(true)
+                .help(field.long_help())
+                .value_parser(move |s: &str| field.parse(s));
+            cmd.subcommand(Command::new(field.name)
+                .about(field.help)
+                .aliases(field.aliases)
+                .arg(value))
+        })
+    }
+
+    fn augment_subcommands_for_update(cmd: Command) -> Command {
+        SetCommand::augment_subcommands(cmd)
+    }
+
+    fn has_subcommand(name: &str) -> bool {
+        fields::find(name).is_some_and(|f| f.writable)
+    }
 }
 
-fn main() {
-    let mut spi = match create_spi() {
-        Ok(spi) => spi,
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let hw = match cli.hw.resolve() {
+        Ok(hw) => hw,
         Err(e) => {
-            println!("Unable to open SPI: {}", e);
-            return
+            eprintln!("Unable to load hardware profile: {}", e);
+            return ExitCode::FAILURE
         },
     };
 
-    let cli = Cli::parse();
-    let mut sx1255_info = SX1255Info::default();
-    get_info(&mut spi, &mut sx1255_info);
+    if let Some(code) = run_offline(&cli, &hw) {
+        return code
+    }
+
+    let mut sx1255 = match Sx1255::open_hw(&hw) {
+        Ok(sx1255) => sx1255,
+        Err(e) => {
+            eprintln!("Unable to open SPI {}: {}", hw.spi_dev, e);
+            return ExitCode::FAILURE
+        },
+    };
+
+    let duplex_file = match &cli.duplex_file {
+        Some(path) => path.clone(),
+        None => user_duplex_file().unwrap_or_default(),
+    };
+    run(&cli, &hw, &duplex_file, &mut sx1255)
+}
+
+// runs the commands that don't need the device
+fn run_offline(cli: &Cli, hw: &HwConfig) -> Option<ExitCode> {
+    match &cli.command {
+        Commands::Diff { file, other: Some(other) } => Some(diff_files(file, other, &hw.reference())),
+        Commands::Channels { channels, command: ChannelCommands::List } => {
+            let table = match load_channels(channels) {
+                Ok(table) => table,
+                Err(e) => {
+                    eprintln!("Error reading channels: {}", e);
+                    return Some(ExitCode::FAILURE)
+                },
+            };
+            for channel in &table.channels {
+                let settings: Vec<_> = channel.settings.iter()
+                    .map(|(field, value)| format!("{}={}", field.name, field.format(*value)))
+                    .collect();
+                println!("{:<16} Rx {:>11.6} MHz  Tx {:>11.6} MHz  {}", channel.name,
+                    channel.rx_freq as f64 / 1e6, channel.tx_freq as f64 / 1e6, settings.join(" "));
+            }
+            Some(ExitCode::SUCCESS)
+        },
+        Commands::Profile { profile_dir, command: ProfileCommands::List } => {
+            let store = profile_store(profile_dir);
+            let profiles = match store.list() {
+                Ok(profiles) => profiles,
+                Err(e) => {
+                    eprintln!("Error listing profiles: {}", e);
+                    return Some(ExitCode::FAILURE)
+                },
+            };
+            if profiles.is_empty() {
+                let dirs: Vec<_> = store.dirs().iter().map(|dir| dir.display().to_string()).collect();
+                println!("No profiles in {}", dirs.join(" or "));
+            }
+            for profile in profiles {
+                println!("{:<20} {}", profile.name, profile.path.display());
+            }
+            Some(ExitCode::SUCCESS)
+        },
+        Commands::Profile { profile_dir, command: ProfileCommands::Show { name } } => {
+            let content = profile_store(profile_dir).find(name).and_then(|profile| {
+                read_to_string(&profile.path)
+                    .map(|content| (profile, content))
+                    .map_err(|e| Sx1255Error::Config(e.to_string()))
+            });
+            match content {
+                Ok((profile, content)) => {
+                    println!("# {}\n{}", profile.path.display(), content.trim_end());
+                    Some(ExitCode::SUCCESS)
+                },
+                Err(e) => {
+                    eprintln!("Error reading profile {}: {}", name, e);
+                    Some(ExitCode::FAILURE)
+                },
+            }
+        },
+        _ => None,
+    }
+}
+
+fn load_channels(channels: &Option<PathBuf>) -> Result<ChannelTable, Sx1255Error> {
+    match channels {
+        Some(path) => ChannelTable::load(path),
+        None => ChannelTable::load(&default_channels_file()),
+    }
+}
+
+fn profile_store(profile_dir: &Option<PathBuf>) -> ProfileStore {
+    match profile_dir {
+        Some(dir) => ProfileStore::new(vec![dir.clone()]),
+        None => ProfileStore::default(),
+    }
+}
+
+// runs the commands that need the device, after run_offline
+fn run<B: RegisterBus>(cli: &Cli, hw: &HwConfig, duplex_file: &Path, sx1255: &mut Sx1255<B>) -> ExitCode {
+    sx1255.set_tx_interlock(!cli.override_tx_bands);
+
+    let mut sx1255_info = match sx1255.read_state() {
+        Ok(sx1255_info) => sx1255_info,
+        Err(e) => {
+            eprintln!("Error reading device state: {}", e);
+            return ExitCode::FAILURE
+        },
+    };
 
     match &cli.command {
-        Commands::Info => {
-            print_info(sx1255_info);
+        Commands::Info { format: Format::Text } => {
+            print_info(sx1255_info, &sx1255.reference());
+            match Duplex::load(duplex_file) {
+                Ok(duplex) => print_duplex(&duplex, &sx1255_info),
+                Err(e) => {
+                    eprintln!("Error reading duplex setting: {}", e);
+                    return ExitCode::FAILURE
+                },
+            }
+        },
+        Commands::Info { format } => {
+            match format_report(&sx1255_info, &sx1255.reference(), *format) {
+                Ok(report) => println!("{}", report.trim_end()),
+                Err(e) => {
+                    eprintln!("Error formatting report: {}", e);
+                    return ExitCode::FAILURE
+                },
+            };
         },
         Commands::Save  { file } => {
             println!("Saving to {}", file.display());
             match write_file(sx1255_info, file) {
                 Ok(_) => {},
                 Err(e) => {
-                    println!("Error writing to {}: {}", file.display(), e);
-                    return
+                    eprintln!("Error writing to {}: {}", file.display(), e);
+                    return ExitCode::FAILURE
                 },
             };
         },
-        Commands::Load { file } => {
+        Commands::Load { file, merge, wait_lock } => {
             println!("Loading from {}", file.display());
-            match read_file(&mut sx1255_info, file) {
-                Ok(_) => {},
+            return load(sx1255, sx1255_info, file, *merge, *wait_lock);
+        },
+        Commands::Profile { profile_dir, command: ProfileCommands::Save { name, force } } => {
+            let path = match profile_store(profile_dir).save_path(name) {
+                Ok(path) => path,
                 Err(e) => {
-                    println!("Error loading from {}: {}", file.display(), e);
-                    return
+                    eprintln!("Error saving profile {}: {}", name, e);
+                    return ExitCode::FAILURE
                 },
             };
-            set_info(&mut spi, sx1255_info);
+            if path.exists() && !force {
+                eprintln!("Profile {} already exists, use --force to replace it", name);
+                return ExitCode::FAILURE
+            }
+            println!("Saving profile {} to {}", name, path.display());
+            if let Err(e) = write_file(sx1255_info, &path) {
+                eprintln!("Error writing to {}: {}", path.display(), e);
+                return ExitCode::FAILURE
+            }
         },
-        Commands::Reset => {
-            println!("Resetting");
-            match reset() {
-                Ok(_) => {},
+        Commands::Profile { profile_dir, command: ProfileCommands::Apply { name, merge, wait_lock } } => {
+            let profile = match profile_store(profile_dir).find(name) {
+                Ok(profile) => profile,
                 Err(e) => {
-                    println!("Error during reset: {}", e);
-                    println!("The pin may be in use by the deprecated sysfs interface.");
-                    println!("Try running: echo 537 > /sys/class/gpio/unexport");
-                    return
+                    eprintln!("Error applying profile {}: {}", name, e);
+                    return ExitCode::FAILURE
                 },
             };
+            println!("Applying profile {} from {}", name, profile.path.display());
+            return load(sx1255, sx1255_info, &profile.path, *merge, *wait_lock);
         },
-        Commands::Set { name } => {
-            match name {
-                SetCommands::DriverEnable { value } => {
-                    println!("Setting driver_enable to {}", value);
-                    sx1255_info.driver_enable = *value;
-                },
-                SetCommands::TxEnable { value } => {
-                    println!("Setting tx_enable to {}", value);
-                    sx1255_info.tx_enable = *value;
-                },
-                SetCommands::RxEnable { value } => {
-                    println!("Setting rx_enable to {}", value);
-                    sx1255_info.rx_enable = *value;
-                },
-                SetCommands::RefEnable { value } => {
-                    println!("Setting ref_enable to {}", value);
-                    sx1255_info.ref_enable = *value;
-                },
-                SetCommands::RxFreq { freq } => {
-                    println!("Setting Rx frequency to {}", *freq);
-                    sx1255_info.rx_freq = *freq;
-                },
-                SetCommands::TxFreq { freq } => {
-                    println!("Setting Tx frequency to {}", *freq);
-                    sx1255_info.tx_freq = *freq;
-                },
-                SetCommands::TxDacGain { gain } => {
-                    println!("Setting Tx DAC gain to {} ({})", *gain, OPTS.tx_dac_gain[*gain as usize]);
-                    sx1255_info.tx_dac_gain = *gain;
-                },
-                SetCommands::TxMixerGain { gain } => {
-                    println!("Setting Tx mixer gain to {} ({})", *gain, OPTS.tx_mixer_gain[*gain as usize]);
-                    sx1255_info.tx_mixer_gain = *gain;
-                },
-                SetCommands::TxMixerTankCap { cap } => {
-                    println!("Setting capacitance in parallel to mixer tank to {} ({})", *cap, OPTS.tx_mixer_tank_cap[*cap as usize]);
-                    sx1255_info.tx_mixer_tank_cap = *cap;
-                },
-                SetCommands::TxPllBw { bw } => {
-                    println!("Setting Tx PLL bandwidth to {} ({} KHz)", *bw, OPTS.tx_pll_bw[*bw as usize]);
-                    sx1255_info.tx_pll_bw = *bw;
-                },
-                SetCommands::TxMixerTankRes { res } => {
-                    println!("Setting resistance in parallel with the mixer tank to {} ({} kΩ)", *res, OPTS.tx_mixer_tank_res[*res as usize]);
-                    sx1255_info.tx_pll_bw = *res;
-                },
-                SetCommands::TxFilterBw { bw } => {
-                    println!("Setting Tx analog filter bandwidth DSB to {} ({} MHz)", *bw, OPTS.tx_filter_bw[*bw as usize]);
-                    sx1255_info.tx_filter_bw = *bw;
-                },
-                SetCommands::TxDacBw { bw } => {
-                    println!("Setting Tx FIR-DAC number of taps to {} ({} actual nubmer of taps)", *bw, OPTS.tx_dac_bw[*bw as usize]);
-                    sx1255_info.tx_dac_bw = *bw;
-                },
-                SetCommands::RxLnaGain { gain } => {
-                    println!("Setting Rx LNA gain to {} ({})", *gain, OPTS.rx_lna_gain[*gain as usize]);
-                    sx1255_info.rx_lna_gain = *gain;
-                },
-                SetCommands::RxPgaGain { gain } => {
-                    println!("Setting Rx PGA gain to {} ({})", *gain, OPTS.rx_pga_gain[*gain as usize]);
-                    sx1255_info.rx_pga_gain = *gain;
-                },
-                SetCommands::RxZin200 { imp } => {
-                    println!("Setting Rx input impedance to {} ({})", *imp, OPTS.rx_zin_200[*imp as usize]);
-                    sx1255_info.rx_zin_200 = *imp;
-                },
-                SetCommands::RxAdcTrim { trim } => {
-                    println!("Setting Rx ADC trim for 36 Mhz reference crystal to {}", *trim);
-                    sx1255_info.rx_adc_trim = *trim;
-                },
-                SetCommands::RxPgaBw { bw } => {
-                    println!("Setting Rx analog roofing filter to {} ({})", *bw, OPTS.rx_pga_bw[*bw as usize]);
-                    sx1255_info.rx_pga_bw = *bw;
-                },
-                SetCommands::RxPllBw { bw } => {
-                    println!("Setting Rx PLL bandwidth to {} ({})", *bw, OPTS.rx_pll_bw[*bw as usize]);
-                    sx1255_info.rx_pll_bw = *bw;
-                },
-                SetCommands::RxAdcTemp { value } => {
-                    println!("Setting Rx ADC temperature measurement mode to {}", *value);
-                    sx1255_info.rx_adc_temp = *value;
-                },
-                SetCommands::IoMap0 { value } => {
-                    println!("Setting mapping of DIO(0) {} ({})", *value, OPTS.iomap0[*value as usize]);
-                    sx1255_info.iomap0 = *value;
-                },
-                SetCommands::IoMap1 { value } => {
-                    println!("Setting mapping of DIO(1) {} ({})", *value, OPTS.iomap1[*value as usize]);
-                    sx1255_info.iomap1 = *value;
-                },
-                SetCommands::IoMap2 { value } => {
-                    println!("Setting mapping of DIO(2) {} ({})", *value, OPTS.iomap2[*value as usize]);
-                    sx1255_info.iomap2= *value;
-                },
-                SetCommands::IoMap3 { value } => {
-                    println!("Setting mapping of DIO(3) {} ({})", *value, OPTS.iomap3[*value as usize]);
-                    sx1255_info.iomap3 = *value;
-                },
-                SetCommands::DigLoopbackEn { value } => {
-                    println!("Setting digital loopback enable to {}", *value);
-                    sx1255_info.dig_loopback_en = *value;
-                },
-                SetCommands::RfLoopbackEn { value } => {
-                    println!("Setting digital loopback enable to {}", *value);
-                    sx1255_info.rf_loopback_en = *value;
-                },
-                SetCommands::IismRxDisable { value } => {
-                    println!("Setting flag to disable IISM Rx (during Tx mode) {}", *value);
-                    sx1255_info.iism_rx_disable = *value;
+        Commands::Ptt { command: PttCommands::On { timeout, wait_lock } } => {
+            // the handler has to be in place before keying, or an
+            // interrupted hold would leave the transmitter on
+            if timeout.is_some() && let Err(e) = stop_on_signal() {
+                eprintln!("Unable to handle signals: {}", e);
+                return ExitCode::FAILURE
+            }
+            if let Err(e) = sx1255.key_tx(Duration::from_millis(*wait_lock)) {
+                eprintln!("Error keying: {}", e);
+                return ExitCode::FAILURE
+            }
+            match timeout {
+                Some(timeout) => return hold_ptt(sx1255, *timeout),
+                None => println!("PTT on"),
+            }
+        },
+        Commands::Ptt { command: PttCommands::Off } => {
+            if let Err(e) = sx1255.unkey_tx() {
+                eprintln!("Error unkeying: {}", e);
+                return ExitCode::FAILURE
+            }
+            println!("PTT off");
+        },
+        Commands::Duplex { command } => {
+            let mut duplex = match Duplex::load(duplex_file) {
+                Ok(duplex) => duplex,
+                Err(e) => {
+                    eprintln!("Error reading duplex setting: {}", e);
+                    return ExitCode::FAILURE
                 },
-                SetCommands::IismTxDisable { value } => {
-                    println!("Setting flag to disable IISM Tx (during Rx mode) {}", *value);
-                    sx1255_info.iism_tx_disable = *value;
+            };
+            let Some(command) = command else {
+                print_duplex(&duplex, &sx1255_info);
+                return ExitCode::SUCCESS
+            };
+            duplex = match command {
+                DuplexCommands::Off => Duplex::default(),
+                DuplexCommands::Simplex => Duplex { shift: Shift::Simplex, ..Duplex::default() },
+                DuplexCommands::Plus { offset } => Duplex { shift: Shift::Plus, offset: *offset, reverse: false },
+                DuplexCommands::Minus { offset } => Duplex { shift: Shift::Minus, offset: *offset, reverse: false },
+                DuplexCommands::Reverse => {
+                    // move Rx to where Tx was, so the pair stays the same
+                    match duplex.tx_freq(sx1255_info.rx_freq) {
+                        Ok(Some(tx_freq)) if tx_freq != sx1255_info.rx_freq => sx1255_info.rx_freq = tx_freq,
+                        Ok(_) => {
+                            eprintln!("Reverse needs a plus or minus split, duplex is {}", duplex);
+                            return ExitCode::FAILURE
+                        },
+                        Err(e) => {
+                            eprintln!("Error reversing duplex: {}", e);
+                            return ExitCode::FAILURE
+                        },
+                    }
+                    Duplex { reverse: !duplex.reverse, ..duplex }
                 },
-                SetCommands::IismMode { mode } => {
-                    println!("Setting IISM mode to {} ({})", *mode, OPTS.iism_mode[*mode as usize]);
-                    sx1255_info.iism_mode = *mode;
+            };
+            if let Err(e) = duplex.apply(&mut sx1255_info) {
+                eprintln!("Error setting duplex: {}", e);
+                return ExitCode::FAILURE
+            }
+            if let Err(e) = sx1255.apply_state(sx1255_info) {
+                eprintln!("Error writing device state: {}", e);
+                return ExitCode::FAILURE
+            }
+            if let Err(e) = duplex.save(duplex_file) {
+                eprintln!("Error saving duplex setting: {}", e);
+                return ExitCode::FAILURE
+            }
+            println!("Duplex set to {}", duplex);
+            for (name, freq) in [("Rx", sx1255_info.rx_freq), ("Tx", sx1255_info.tx_freq)] {
+                if let Ok(tuning) = sx1255.reference().tune(freq) {
+                    print_tuning(name, &tuning);
+                }
+            }
+        },
+        Commands::Tune { name, channels, wait_lock } => {
+            let channel = match load_channels(channels) {
+                Ok(table) => table.find(name).cloned().ok_or_else(|| format!("no channel named {}", name)),
+                Err(e) => Err(e.to_string()),
+            };
+            let channel = match channel {
+                Ok(channel) => channel,
+                Err(e) => {
+                    eprintln!("Error tuning to {}: {}", name, e);
+                    return ExitCode::FAILURE
                 },
-                SetCommands::IismClkDiv { factor } => {
-                    println!("Setting XTAL/CLK_OUT division factor to {} ({})", *factor, OPTS.iism_clk_div[*factor as usize]);
-                    sx1255_info.iism_clk_div = *factor;
+            };
+            println!("Tuning to {}", channel.name);
+            for (field, value) in &channel.settings {
+                println!("Setting {} to {}", field.name, field.describe(*value));
+            }
+            if let Err(e) = channel.apply(&mut sx1255_info) {
+                eprintln!("Error tuning to {}: {}", name, e);
+                return ExitCode::FAILURE
+            }
+            if let Err(e) = sx1255.apply_state(sx1255_info) {
+                eprintln!("Error writing device state: {}", e);
+                return ExitCode::FAILURE
+            }
+            for (name, freq) in [("Rx", channel.rx_freq), ("Tx", channel.tx_freq)] {
+                if let Ok(tuning) = sx1255.reference().tune(freq) {
+                    print_tuning(name, &tuning);
+                }
+            }
+            if let Some(timeout) = wait_lock {
+                return wait_for_lock(sx1255, *timeout);
+            }
+        },
+        Commands::Diff { other: Some(_), .. }
+        | Commands::Channels { .. }
+        | Commands::Profile { command: ProfileCommands::List | ProfileCommands::Show { .. }, .. } => {
+            unreachable!("handled by run_offline")
+        },
+        Commands::Reset => {
+            println!("Resetting");
+            match reset(hw) {
+                Ok(_) => {},
+                Err(e) => {
+                    eprintln!("Error during reset: {}", e);
+                    eprintln!("The pin may be in use by the deprecated sysfs interface.");
+                    eprintln!("Try running: echo 537 > /sys/class/gpio/unexport");
+                    return ExitCode::FAILURE
                 },
-                SetCommands::R { r_str } => {
-                    let r: u32 = r_str.parse().unwrap();
-                    println!("Setting interpolation/decimation factor to {}", r);
-                    sx1255_info.r = r;
+            };
+        },
+        Commands::Rate { target: None, .. } => {
+            print_rates(&Rates::of(&sx1255_info, &sx1255.reference()));
+        },
+        Commands::Rate { target: Some(target), apply } => {
+            let rates = match plan(&sx1255.reference(), *target as f64, sx1255_info.iism_mode) {
+                Some(rates) => rates,
+                None => {
+                    eprintln!("No IISM configuration works with a {} Hz reference", sx1255.reference().xtal_freq);
+                    return ExitCode::FAILURE
                 },
-                SetCommands::IismTruncation { mode } => {
-                    println!("Setting IISM truncation mode in Rx and Tx to {} ({})", *mode, OPTS.iism_truncation[*mode as usize]);
-                    sx1255_info.iism_truncation = *mode;
+            };
+            println!("Closest to {} Hz (error {:+.3} Hz):\n", target, rates.sample_rate - *target as f64);
+            print_rates(&rates);
+            if *apply {
+                rates.apply(&mut sx1255_info);
+                if let Err(e) = sx1255.apply_state(sx1255_info) {
+                    eprintln!("Error writing device state: {}", e);
+                    return ExitCode::FAILURE
+                }
+                println!("\nApplied");
+            }
+        },
+        Commands::Diff { file, other: None } => {
+            let loaded = match load_settled(file, &sx1255.reference()) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    eprintln!("Error loading from {}: {}", file.display(), e);
+                    return ExitCode::FAILURE
                 },
             };
-            set_info(&mut spi, sx1255_info);
+            return print_diff("device", &sx1255_info, &file.display().to_string(), &loaded);
+        },
+        Commands::Watch { interval, count, json, hook } => {
+            if let Err(e) = watch(sx1255, Duration::from_millis(*interval), *count, *json, hook) {
+                eprintln!("Error reading device status: {}", e);
+                return ExitCode::FAILURE
+            }
+        },
+        Commands::Reg { command } => {
+            if let Err(e) = run_reg(command, sx1255) {
+                eprintln!("Register access failed: {}", e);
+                return ExitCode::FAILURE
+            }
+        },
+        Commands::Set { wait_lock, assignments, name } => {
+            let assignments = match name {
+                Some(SetCommand { field, value }) => vec![(*field, *value)],
+                None => assignments.clone(),
+            };
+            let mut tunings = Vec::new();
+            for (i, (field, value)) in assignments.iter().enumerate() {
+                if assignments[..i].iter().any(|(other, _)| other.name == field.name) {
+                    eprintln!("{} is set more than once", field.name);
+                    return ExitCode::FAILURE
+                }
+                println!("Setting {} to {}", field.name, field.describe(*value));
+                if let Err(e) = field.apply(&mut sx1255_info, *value) {
+                    eprintln!("Error setting {}: {}", field.name, e);
+                    return ExitCode::FAILURE
+                }
+                match field.name {
+                    "rx_freq" => tunings.push(("Rx", sx1255.reference().tune(*value))),
+                    "tx_freq" => tunings.push(("Tx", sx1255.reference().tune(*value))),
+                    _ => {},
+                };
+            }
+            // Tx follows a retuned Rx unless it is set too
+            let set = |name| assignments.iter().any(|(field, _)| field.name == name);
+            if set("rx_freq") && !set("tx_freq") {
+                let tx_freq = Duplex::load(duplex_file)
+                    .and_then(|duplex| duplex.tx_freq(sx1255_info.rx_freq).map(|tx_freq| (duplex, tx_freq)));
+                match tx_freq {
+                    Ok((duplex, Some(tx_freq))) => {
+                        println!("Setting tx_freq to {} Hz (duplex {})", tx_freq, duplex);
+                        sx1255_info.tx_freq = tx_freq;
+                        tunings.push(("Tx", sx1255.reference().tune(tx_freq)));
+                    },
+                    Ok((_, None)) => {},
+                    Err(e) => {
+                        eprintln!("Error following duplex: {}", e);
+                        return ExitCode::FAILURE
+                    },
+                }
+            }
+            // everything goes out in one verified update
+            if let Err(e) = sx1255.apply_state(sx1255_info) {
+                eprintln!("Error writing device state: {}", e);
+                return ExitCode::FAILURE
+            }
+            for (name, tuning) in tunings {
+                if let Ok(tuning) = tuning {
+                    print_tuning(name, &tuning);
+                }
+            }
+            if let Some(timeout) = wait_lock {
+                return wait_for_lock(sx1255, *timeout);
+            }
+        },
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn print_rates(rates: &Rates) {
+    let show = |name, value| {
+        let field = fields::find(name).expect("IISM fields exist");
+        println!("{:>32}: {}", field.label, field.describe(value));
+    };
+    // whole rates as they are, the rest to the mHz
+    let hz = |rate: Option<f64>| match rate {
+        Some(rate) if rate.fract() == 0.0 => format!("{} Hz", rate),
+        Some(rate) => format!("{:.3} Hz", rate),
+        None => "not defined".to_string(),
+    };
+    show("iism_mode", rates.mode as u32);
+    show("iism_clk_div", rates.clk_div as u32);
+    show("r", rates.r);
+    println!("{:>32}: {}", "Bit clock (CLK_OUT)", hz(rates.bit_clock));
+    println!("{:>32}: {}", "Word clock", hz(rates.word_clock));
+    println!("{:>32}: {}", "Bits per sample", rates.bits_per_sample.map_or("not defined".to_string(), |bits| bits.to_string()));
+    println!("{:>32}: {}", "I/Q sample rate", hz(Some(rates.sample_rate)));
+
+    match (rates.valid(), rates.word_clock, rates.bits_per_sample) {
+        (true, Some(word_clock), Some(bits)) if bits == 16.0 || bits == 32.0 => {
+            println!("\nStream with: sx1255-pub --sample-rate {} --sample-format S{}_LE", word_clock.round(), bits);
+        },
+        (true, _, _) => {},
+        (false, _, _) => println!("\nThe IISM can only frame a whole number of 8-32 bits per sample in modes B1 and B2"),
+    }
+}
+
+// how often a held PTT checks for the timeout or a signal
+const PTT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// set by Ctrl-C, SIGTERM or SIGHUP while holding PTT
+static STOP: AtomicBool = AtomicBool::new(false);
+static STOP_HANDLER: OnceLock<Result<(), String>> = OnceLock::new();
+
+fn stop_on_signal() -> Result<(), String> {
+    STOP.store(false, Ordering::SeqCst);
+    STOP_HANDLER.get_or_init(|| {
+        ctrlc::set_handler(|| STOP.store(true, Ordering::SeqCst)).map_err(|e| e.to_string())
+    }).clone()
+}
+
+// keeps the transmitter keyed until the timeout or a signal, then drops it
+fn hold_ptt<B: RegisterBus>(sx1255: &mut Sx1255<B>, timeout: Duration) -> ExitCode {
+    println!("PTT on, dropping it in {} s", timeout.as_secs_f64());
+    let start = Instant::now();
+    while start.elapsed() < timeout && !STOP.load(Ordering::SeqCst) {
+        sleep(PTT_POLL_INTERVAL.min(timeout.saturating_sub(start.elapsed())));
+    }
+    if let Err(e) = sx1255.unkey_tx() {
+        eprintln!("Error unkeying: {}", e);
+        return ExitCode::FAILURE
+    }
+    if STOP.load(Ordering::SeqCst) {
+        println!("PTT off, interrupted after {:.1} s", start.elapsed().as_secs_f64());
+    } else {
+        println!("PTT off, timed out after {} s", timeout.as_secs_f64());
+    }
+    ExitCode::SUCCESS
+}
+
+fn print_duplex(duplex: &Duplex, sx1255_info: &SX1255Info) {
+    println!("Duplex\n");
+    println!("{:>32}: {}", "Duplex setting", duplex);
+    println!("{:>32}: {:+} Hz", "Tx minus Rx", sx1255_info.tx_freq as i64 - sx1255_info.rx_freq as i64);
+    println!();
+}
+
+fn print_tuning(name: &str, tuning: &Tuning) {
+    println!("{} tuned to {:.3} Hz (requested {} Hz, error {:+.3} Hz)",
+        name, tuning.actual, tuning.requested, tuning.error());
+}
+
+// loads a file, or merges it with `sx1255_info`, and writes it to the device
+fn load<B: RegisterBus>(sx1255: &mut Sx1255<B>, mut sx1255_info: SX1255Info, file: &Path,
+    merge: bool, wait_lock: Option<u64>) -> ExitCode {
+    let result = if merge {
+        merge_file(&mut sx1255_info, file)
+    } else {
+        read_file(&mut sx1255_info, file)
+    };
+    match result {
+        Ok(_) => {},
+        Err(e) => {
+            eprintln!("Error loading from {}: {}", file.display(), e);
+            return ExitCode::FAILURE
+        },
+    };
+    if let Err(e) = sx1255.apply_state(sx1255_info) {
+        eprintln!("Error writing device state: {}", e);
+        return ExitCode::FAILURE
+    }
+    match wait_lock {
+        Some(timeout) => wait_for_lock(sx1255, timeout),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn wait_for_lock<B: RegisterBus>(sx1255: &mut Sx1255<B>, timeout: u64) -> ExitCode {
+    match sx1255.wait_lock(Duration::from_millis(timeout)) {
+        Ok(_) => {
+            println!("PLL locked");
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("Error waiting for PLL lock: {}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+// reads a file and rounds it to what the registers can hold, so only
+// settings that would really change show up as differences
+fn load_settled(file: &Path, reference: &Reference) -> Result<SX1255Info, Sx1255Error> {
+    let mut sx1255_info = SX1255Info::default();
+    read_file(&mut sx1255_info, file).map_err(|e| Sx1255Error::Config(e.to_string()))?;
+    let regs = sx1255_info.to_registers(reference)?;
+    Ok(SX1255Info::from_registers(&regs, reference))
+}
+
+fn diff_files(file: &Path, other: &Path, reference: &Reference) -> ExitCode {
+    let mut loaded = Vec::new();
+    for file in [file, other] {
+        match load_settled(file, reference) {
+            Ok(sx1255_info) => loaded.push(sx1255_info),
+            Err(e) => {
+                eprintln!("Error loading from {}: {}", file.display(), e);
+                return ExitCode::FAILURE
+            },
+        };
+    }
+    print_diff(&file.display().to_string(), &loaded[0], &other.display().to_string(), &loaded[1])
+}
+
+// prints the settings that differ, fails if there are any
+fn print_diff(old_name: &str, old: &SX1255Info, new_name: &str, new: &SX1255Info) -> ExitCode {
+    let changes = compare_info(old, new);
+    if changes.is_empty() {
+        println!("{} and {} match", old_name, new_name);
+        return ExitCode::SUCCESS
+    }
+    println!("--- {}\n+++ {}", old_name, new_name);
+    for change in changes {
+        println!("{}: {} -> {}", change.field, change.expected, change.actual);
+    }
+    ExitCode::FAILURE
+}
+
+// polls the status flags, printing each change and running its hooks
+fn watch<B: RegisterBus>(sx1255: &mut Sx1255<B>, interval: Duration, count: Option<u64>,
+    json: bool, hooks: &[(String, String)]) -> Result<(), Sx1255Error> {
+    let mut previous = None;
+    let mut polls = 0;
+    while count.is_none_or(|count| polls < count) {
+        if polls > 0 {
+            sleep(interval);
+        }
+        polls += 1;
+
+        let status = sx1255.read_status()?;
+        let time = Local::now();
+        for event in status.events(previous.as_ref()) {
+            if json {
+                println!("{}", serde_json::json!({
+                    "time":    time.to_rfc3339_opts(SecondsFormat::Millis, false),
+                    "event":   event.name,
+                    "field":   event.field,
+                    "value":   event.value,
+                    "message": event.message,
+                }));
+            } else {
+                println!("{} {}", time.format("%Y-%m-%d %H:%M:%S%.3f"), event.message);
+            }
+            // the first poll only reports where things stand
+            if previous.is_some() {
+                run_hooks(hooks, &event);
+            }
+        }
+        previous = Some(status);
+    }
+    Ok(())
+}
+
+fn run_hooks(hooks: &[(String, String)], event: &Event) {
+    for (_, command) in hooks.iter().filter(|(name, _)| name == event.name) {
+        let result = process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("SX1255_EVENT", event.name)
+            .env("SX1255_FIELD", event.field)
+            .env("SX1255_VALUE", event.value.to_string())
+            .status();
+        match result {
+            Ok(status) if status.success() => {},
+            Ok(status) => eprintln!("Hook for {} failed: {}", event.name, status),
+            Err(e) => eprintln!("Unable to run hook for {}: {}", event.name, e),
+        }
+    }
+}
+
+fn run_reg<B: RegisterBus>(command: &RegCommands, sx1255: &mut Sx1255<B>) -> Result<(), Sx1255Error> {
+    match command {
+        RegCommands::Read { addr, count } => {
+            let mut vals = vec![0_u8; *count as usize];
+            sx1255.read_regs(*addr, &mut vals)?;
+            for (i, val) in vals.iter().enumerate() {
+                let addr = *addr as usize + i;
+                println!("0x{:02X} {:<12} 0x{:02X} {:08b}", addr, REG_NAMES[addr], val, val);
+            }
+        },
+        RegCommands::Write { addr, values } => {
+            let mut old = vec![0_u8; values.len()];
+            sx1255.read_regs(*addr, &mut old)?;
+            sx1255.write_regs(*addr, values)?;
+            let mut new = vec![0_u8; values.len()];
+            sx1255.read_regs(*addr, &mut new)?;
+            for i in 0..values.len() {
+                let addr = *addr as usize + i;
+                println!("0x{:02X} {:<12} 0x{:02X} -> 0x{:02X} (read back 0x{:02X})",
+                    addr, REG_NAMES[addr], old[i], values[i], new[i]);
+            }
         },
+        RegCommands::Dump => {
+            let mut regs = [0_u8; NUM_REGS];
+            sx1255.read_regs(0, &mut regs)?;
+            print_dump(&regs, &sx1255.reference());
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sx1255::MockBus;
+    use sx1255::regs::{REG_STAT, ClkOut, DacClock, DacGain, IismMode, LnaGain, PgaBw, PllBw, RxZin, TankRes, Truncation};
+
+    fn run_args(bus: &mut MockBus, args: &[&str]) -> ExitCode {
+        let cli = Cli::try_parse_from(["sx1255-config"].iter().chain(args)).unwrap();
+        // keep away from the duplex setting of whoever runs the tests
+        let duplex_file = cli.duplex_file.clone().unwrap_or_else(|| temp_file("no-duplex"));
+        let hw = HwConfig::default();
+        // dispatched the way main does it
+        run_offline(&cli, &hw).unwrap_or_else(|| run(&cli, &hw, &duplex_file, &mut Sx1255::new(bus)))
+    }
+
+    fn state(bus: &mut MockBus) -> SX1255Info {
+        Sx1255::new(bus).read_state().unwrap()
+    }
+
+    type SetCase = (&'static [&'static str], fn(&SX1255Info) -> bool);
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sx1255-config-{}-{}.toml", std::process::id(), name))
+    }
+
+    #[test]
+    fn hw_args_override_profile() {
+        let path = temp_file("hw");
+        std::fs::write(&path, "spi_dev = \"/dev/spidev1.0\"\nreset_line = 5\n").unwrap();
+        let cli = Cli::try_parse_from([
+            "sx1255-config", "info", "--hw-config", path.to_str().unwrap(), "--reset-line", "7",
+        ]).unwrap();
+        let hw = cli.hw.resolve().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hw.spi_dev, "/dev/spidev1.0");
+        assert_eq!(hw.reset_line, 7);
+        assert!(cli.hw.resolve().is_err());
+    }
+
+    #[test]
+    fn set_freq_rounds_to_nearest_step() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["set", "rx_freq", "435006000"]), ExitCode::SUCCESS);
+        assert_eq!(bus.regs[1..4], [0xD9, 0x80, 0xC5]);
+        assert_eq!(state(&mut bus).rx_freq, 435006012);
+
+        // writing back what was read must not walk the frequency down
+        assert_eq!(run_args(&mut bus, &["set", "rx_enable", "true"]), ExitCode::SUCCESS);
+        assert_eq!(bus.regs[1..4], [0xD9, 0x80, 0xC5]);
+    }
+
+    #[test]
+    fn info_formats() {
+        let mut bus = MockBus::new();
+        run_args(&mut bus, &["set", "rx_freq", "435000000"]);
+        let sx1255_info = state(&mut bus);
+        let reference = Reference::default();
+
+        let json: serde_json::Value = serde_json::from_str(
+            &format_report(&sx1255_info, &reference, Format::Json).unwrap()).unwrap();
+        assert_eq!(json["schema"], 1);
+        assert_eq!(json["state"]["rx_freq"], 435000000);
+        assert_eq!(json["state"]["version"], 0x11);
+        assert_eq!(json["state"]["rx_lna_gain"], 1);
+        assert_eq!(json["state"]["xosc_ready"], true);
+        assert_eq!(json["decoded"]["rx_lna_gain"], "1 (G1 = highest gain power - 0 dB)");
+        assert_eq!(json["reference"]["xtal_freq"], 32000000);
+
+        let toml: toml::Table = toml::from_str(
+            &format_report(&sx1255_info, &reference, Format::Toml).unwrap()).unwrap();
+        assert_eq!(toml["state"]["rx_freq"].as_integer(), Some(435000000));
+
+        let yaml: serde_yaml::Value = serde_yaml::from_str(
+            &format_report(&sx1255_info, &reference, Format::Yaml).unwrap()).unwrap();
+        assert_eq!(yaml["decoded"]["version"].as_str(), Some("0x11"));
+    }
+
+    #[test]
+    fn reg_commands() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["reg", "read", "0x0C", "3"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["reg", "write", "0x0C", "0x5F"]), ExitCode::SUCCESS);
+        assert_eq!(bus.regs[0x0C], 0x5F);
+        assert_eq!(run_args(&mut bus, &["reg", "write", "1", "0xD9", "0x80", "0x00"]), ExitCode::SUCCESS);
+        assert_eq!(state(&mut bus).rx_freq, 435000000);
+        assert_eq!(run_args(&mut bus, &["reg", "dump"]), ExitCode::SUCCESS);
+
+        assert_eq!(run_args(&mut bus, &["reg", "read", "0x13", "2"]), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &["reg", "write", "0x13", "1", "2"]), ExitCode::FAILURE);
+        assert!(Cli::try_parse_from(["sx1255-config", "reg", "read", "0x14"]).is_err());
+        assert!(Cli::try_parse_from(["sx1255-config", "reg", "write", "0", "256"]).is_err());
+    }
+
+    #[test]
+    fn set_commands() {
+        let cases: &[SetCase] = &[
+            (&["tx_freq=435M", "driver_enable=true"], |i| i.driver_enable),
+            (&["tx_enable", "true"], |i| i.tx_enable),
+            (&["rx_enable", "true"], |i| i.rx_enable),
+            (&["ref_enable", "false"], |i| !i.ref_enable),
+            (&["rx_freq", "435000000"], |i| i.rx_freq == 435000000),
+            (&["tx_freq", "438000000"], |i| i.tx_freq == 438000000),
+            (&["tx_dac_gain", "1"], |i| i.tx_dac_gain == DacGain::Minus6),
+            (&["tx_mixer_gain", "14"], |i| i.tx_mixer_gain == 14),
+            (&["tx_mixer_tank_cap", "5"], |i| i.tx_mixer_tank_cap == 5),
+            (&["tx_mixer_tank_res", "2"], |i| i.tx_mixer_tank_res == TankRes::R1320 && i.tx_pll_bw == PllBw::Bw300k),
+            (&["tx_pll_bw", "1"], |i| i.tx_pll_bw == PllBw::Bw150k),
+            (&["tx_filter_bw", "9"], |i| i.tx_filter_bw == 9),
+            (&["tx_dac_bw", "5"], |i| i.tx_dac_bw == 5),
+            (&["rx_lna_gain", "3"], |i| i.rx_lna_gain == LnaGain::G3),
+            (&["rx_pga_gain", "7"], |i| i.rx_pga_gain == 7),
+            (&["rx_zin200", "0"], |i| i.rx_zin_200 == RxZin::Ohm50),
+            (&["rx_adc_bw", "2"], |i| i.rx_adc_bw == 2),
+            (&["rx_adc_trim", "2"], |i| i.rx_adc_trim == 2),
+            (&["rx_pga_bw", "3"], |i| i.rx_pga_bw == PgaBw::Bw500k),
+            (&["rx_pll_bw", "0"], |i| i.rx_pll_bw == PllBw::Bw75k),
+            (&["rx_adc_temp", "true"], |i| i.rx_adc_temp),
+            (&["io_map0", "3"], |i| i.iomap0 == 3),
+            (&["io_map1", "1"], |i| i.iomap1 == 1),
+            (&["io_map2", "2"], |i| i.iomap2 == 2),
+            (&["io_map3", "3"], |i| i.iomap3 == 3),
+            (&["iomap3", "2"], |i| i.iomap3 == 2),
+            (&["dig_loopback_en", "true"], |i| i.dig_loopback_en),
+            (&["rf_loopback_en", "true"], |i| i.rf_loopback_en),
+            (&["ckout_enable", "0"], |i| i.ckout_enable == ClkOut::Disabled),
+            (&["ck_select_tx_dac", "1"], |i| i.ck_select_tx_dac == DacClock::External),
+            (&["iism_rx_disable", "true"], |i| i.iism_rx_disable),
+            (&["iism_tx_disable", "true"], |i| i.iism_tx_disable),
+            (&["iism_mode", "2"], |i| i.iism_mode == IismMode::B2),
+            (&["iism_clk_div", "5"], |i| i.iism_clk_div == 5),
+            (&["r", "1728"], |i| i.r == 1728),
+            (&["iism_truncation", "1"], |i| i.iism_truncation == Truncation::Lsb),
+            (&["rx_freq", "435.125M"], |i| i.rx_freq == 435125000),
+            (&["tx_mixer_gain", "-9.5dB"], |i| i.tx_mixer_gain == 14),
+            (&["rx_pga_bw", "750k"], |i| i.rx_pga_bw == PgaBw::Bw750k),
+            (&["tx_pll_bw", "150k"], |i| i.tx_pll_bw == PllBw::Bw150k),
+            (&["tx_mixer_tank_res", "1.65k"], |i| i.tx_mixer_tank_res == TankRes::R1650),
+        ];
+        for (args, check) in cases {
+            let mut bus = MockBus::new();
+            // locked, so the PA driver can be keyed
+            bus.regs[REG_STAT as usize] = 0b0101;
+            assert_eq!(run_args(&mut bus, &[&["set"], *args].concat()), ExitCode::SUCCESS);
+            assert!(check(&state(&mut bus)), "set {}", args.join(" "));
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let file = temp_file("save_and_load");
+        let mut bus = MockBus::new();
+        run_args(&mut bus, &["set", "rx_freq", "435000000"]);
+        run_args(&mut bus, &["set", "tx_freq", "438000000"]);
+        run_args(&mut bus, &["set", "iism_mode", "2"]);
+        run_args(&mut bus, &["set", "r", "256"]);
+        run_args(&mut bus, &["set", "iism_truncation", "1"]);
+        run_args(&mut bus, &["set", "ckout_enable", "0"]);
+        run_args(&mut bus, &["save", file.to_str().unwrap()]);
+
+        let mut loaded = MockBus::new();
+        run_args(&mut loaded, &["load", file.to_str().unwrap()]);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(loaded.regs, bus.regs);
+    }
+
+    #[test]
+    fn diff_files_and_device() {
+        let file = temp_file("diff");
+        let other = temp_file("diff_other");
+        let mut bus = MockBus::new();
+        run_args(&mut bus, &["set", "rx_freq", "435000000"]);
+        run_args(&mut bus, &["save", file.to_str().unwrap()]);
+        assert_eq!(run_args(&mut bus, &["diff", file.to_str().unwrap()]), ExitCode::SUCCESS);
+
+        // a frequency that rounds to the same step isn't a difference
+        std::fs::write(&other, std::fs::read_to_string(&file).unwrap()
+            .replace("rx_freq = 435000000", "rx_freq = 435000010")).unwrap();
+        assert_eq!(run_args(&mut bus, &["diff", file.to_str().unwrap(), other.to_str().unwrap()]), ExitCode::SUCCESS);
+
+        run_args(&mut bus, &["set", "rx_lna_gain", "3"]);
+        assert_eq!(run_args(&mut bus, &["diff", file.to_str().unwrap()]), ExitCode::FAILURE);
+        run_args(&mut bus, &["save", other.to_str().unwrap()]);
+        assert_eq!(run_args(&mut bus, &["diff", file.to_str().unwrap(), other.to_str().unwrap()]), ExitCode::FAILURE);
+
+        let changes = compare_info(
+            &load_settled(&file, &Reference::default()).unwrap(),
+            &load_settled(&other, &Reference::default()).unwrap());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "rx_lna_gain");
+        assert_eq!(changes[0].actual, "3 (G3 = highest gain power - 12 dB)");
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(run_args(&mut bus, &["diff", file.to_str().unwrap()]), ExitCode::FAILURE);
+        std::fs::remove_file(&other).unwrap();
+    }
+
+    #[test]
+    fn watch_polls_status() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["watch", "-i", "1", "-n", "3"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["watch", "-i", "1", "-n", "2", "--json",
+            "--hook", "tx_unlock=true", "--hook", "rx_lock=echo $SX1255_EVENT"]), ExitCode::SUCCESS);
+        assert!(Cli::try_parse_from(["sx1255-config", "watch", "--hook", "unlock=true"]).is_err());
+        assert!(Cli::try_parse_from(["sx1255-config", "watch", "--hook", "tx_unlock"]).is_err());
+    }
+
+    #[test]
+    fn wait_lock_fails_when_unlocked() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["set", "--wait-lock", "5", "rx_freq", "435000000"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["set", "rx_enable", "true", "--wait-lock", "5"]), ExitCode::FAILURE);
+        assert!(state(&mut bus).rx_enable);
+
+        let file = temp_file("wait_lock");
+        run_args(&mut bus, &["save", file.to_str().unwrap()]);
+        let mut loaded = MockBus::new();
+        assert_eq!(run_args(&mut loaded, &["load", file.to_str().unwrap(), "--wait-lock", "5"]), ExitCode::FAILURE);
+        loaded.regs[REG_STAT as usize] = 0b0111;
+        assert_eq!(run_args(&mut loaded, &["load", file.to_str().unwrap(), "--wait-lock", "5"]), ExitCode::SUCCESS);
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn ptt_sequences_and_times_out() {
+        let mut bus = MockBus::new();
+        // an unlocked Tx PLL leaves the transmitter off
+        assert_eq!(run_args(&mut bus, &["ptt", "on", "--wait-lock", "5"]), ExitCode::FAILURE);
+        let sx1255_info = state(&mut bus);
+        assert!(!sx1255_info.tx_enable && !sx1255_info.driver_enable);
+
+        bus.regs[REG_STAT as usize] = 0b0101;
+        assert_eq!(run_args(&mut bus, &["ptt", "on"]), ExitCode::FAILURE);
+        assert!(!state(&mut bus).tx_enable);
+        assert_eq!(run_args(&mut bus, &["set", "tx_freq", "435M"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["ptt", "on"]), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        assert!(sx1255_info.tx_enable && sx1255_info.driver_enable);
+        assert_eq!(run_args(&mut bus, &["ptt", "off"]), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        assert!(!sx1255_info.tx_enable && !sx1255_info.driver_enable);
+
+        // an enabled Rx that hasn't locked doesn't hold up keying
+        assert_eq!(run_args(&mut bus, &["set", "rx_enable", "true"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["ptt", "on", "--wait-lock", "5"]), ExitCode::SUCCESS);
+        assert!(state(&mut bus).driver_enable);
+        assert_eq!(run_args(&mut bus, &["ptt", "off"]), ExitCode::SUCCESS);
+
+        let start = Instant::now();
+        assert_eq!(run_args(&mut bus, &["ptt", "on", "--timeout", "0.05"]), ExitCode::SUCCESS);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(!state(&mut bus).driver_enable);
+        assert!(Cli::try_parse_from(["sx1255-config", "ptt", "on", "--timeout", "0"]).is_err());
+        assert!(Cli::try_parse_from(["sx1255-config", "ptt", "off", "--timeout", "5"]).is_err());
+    }
+
+    #[test]
+    fn tx_interlock() {
+        let mut bus = MockBus::new();
+        // the default Tx frequency is outside 70 cm
+        assert_eq!(run_args(&mut bus, &["set", "driver_enable", "true"]), ExitCode::FAILURE);
+        assert_eq!(bus.regs, MockBus::new().regs);
+        assert_eq!(run_args(&mut bus, &["reg", "write", "0", "0x09"]), ExitCode::FAILURE);
+        assert_eq!(bus.regs, MockBus::new().regs);
+
+        // the PA driver only comes on once the Tx PLL has locked
+        assert_eq!(run_args(&mut bus, &["set", "tx_freq=435M", "tx_enable=true", "driver_enable=true"]), ExitCode::FAILURE);
+        let sx1255_info = state(&mut bus);
+        assert!(sx1255_info.tx_enable && !sx1255_info.driver_enable);
+        bus.regs[REG_STAT as usize] = 0b0101;
+        assert_eq!(run_args(&mut bus, &["set", "tx_freq=435M", "tx_enable=true", "driver_enable=true"]), ExitCode::SUCCESS);
+        assert!(state(&mut bus).driver_enable);
+        bus.regs[REG_STAT as usize] = 0b0100;
+        assert_eq!(run_args(&mut bus, &["set", "tx_freq", "460M"]), ExitCode::FAILURE);
+        assert!(state(&mut bus).tx_freq.abs_diff(435000000) < 10);
+
+        let file = temp_file("interlock");
+        assert_eq!(run_args(&mut bus, &["save", file.to_str().unwrap()]), ExitCode::SUCCESS);
+        let content = read_to_string(&file).unwrap().replace("tx_freq = 435000000", "tx_freq = 460000000");
+        std::fs::write(&file, content).unwrap();
+        assert_eq!(run_args(&mut bus, &["load", file.to_str().unwrap()]), ExitCode::FAILURE);
+        // retuning a keyed Tx waits for it to lock again
+        assert_eq!(run_args(&mut bus, &["load", file.to_str().unwrap(), "--override-tx-bands"]), ExitCode::FAILURE);
+        assert!(!state(&mut bus).driver_enable);
+        bus.regs[REG_STAT as usize] = 0b0101;
+        assert_eq!(run_args(&mut bus, &["load", file.to_str().unwrap(), "--override-tx-bands"]), ExitCode::SUCCESS);
+        assert!(state(&mut bus).tx_freq.abs_diff(460000000) < 10);
+        std::fs::remove_file(&file).unwrap();
+
+        // unkeying is always allowed
+        assert_eq!(run_args(&mut bus, &["set", "driver_enable", "false"]), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn rate_plans_and_applies() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["rate"]), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &["rate", "--target", "96000"]), ExitCode::SUCCESS);
+        assert_eq!(bus.regs, MockBus::new().regs);
+
+        assert_eq!(run_args(&mut bus, &["rate", "--target", "250000", "--apply"]), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        assert_eq!((sx1255_info.iism_mode, sx1255_info.iism_clk_div, sx1255_info.r), (IismMode::B2, 2, 128));
+        assert!(Cli::try_parse_from(["sx1255-config", "rate", "--apply"]).is_err());
+    }
+
+    #[test]
+    fn batch_set() {
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &["set", "rx_freq=435M", "rx_lna_gain=1", "rx_enable=true", "io_map0=3"]), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        assert_eq!(sx1255_info.rx_freq, 435000000);
+        assert_eq!(sx1255_info.rx_lna_gain, LnaGain::G1);
+        assert!(sx1255_info.rx_enable);
+        assert_eq!(sx1255_info.iomap0, 3);
+
+        // nothing is written unless every setting is valid
+        let regs = bus.regs;
+        assert!(Cli::try_parse_from(["sx1255-config", "set", "rx_enable=false", "rx_lna_gain=9"]).is_err());
+        assert!(Cli::try_parse_from(["sx1255-config", "set", "rx_enable=false", "version=1"]).is_err());
+        assert!(Cli::try_parse_from(["sx1255-config", "set", "rx_enable"]).is_err());
+        assert_eq!(run_args(&mut bus, &["set", "rx_enable=false", "rx_enable=true"]), ExitCode::FAILURE);
+        assert!(Cli::try_parse_from(["sx1255-config", "set", "rx_enable=false", "rx_lna_gain", "3"]).is_err());
+        assert_eq!(bus.regs, regs);
+    }
+
+    #[test]
+    fn load_merge_and_include() {
+        let base = temp_file("base");
+        let overlay = temp_file("overlay");
+        let mut bus = MockBus::new();
+        run_args(&mut bus, &["set", "rx_freq=435M", "tx_freq=438M", "iism_mode=2"]);
+        run_args(&mut bus, &["save", base.to_str().unwrap()]);
+        let base_name = base.file_name().unwrap().to_str().unwrap();
+
+        // an overlay starts from its includes rather than the defaults
+        std::fs::write(&overlay, format!("include = [\"{}\"]\nrx_lna_gain = 3\n", base_name)).unwrap();
+        let mut loaded = MockBus::new();
+        assert_eq!(run_args(&mut loaded, &["load", overlay.to_str().unwrap()]), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut loaded);
+        assert_eq!(sx1255_info.tx_freq, 438000000);
+        assert_eq!(sx1255_info.iism_mode, IismMode::B2);
+        assert_eq!(sx1255_info.rx_lna_gain, LnaGain::G3);
+
+        // merging only touches the keys in the file
+        std::fs::write(&overlay, "rx_pga_gain = 7\n").unwrap();
+        assert_eq!(run_args(&mut loaded, &["load", "--merge", overlay.to_str().unwrap()]), ExitCode::SUCCESS);
+        let merged = state(&mut loaded);
+        assert_eq!((merged.rx_pga_gain, merged.rx_lna_gain, merged.tx_freq), (7, LnaGain::G3, 438000000));
+        assert_eq!(run_args(&mut loaded, &["load", overlay.to_str().unwrap()]), ExitCode::SUCCESS);
+        assert_ne!(state(&mut loaded).tx_freq, 438000000);
+
+        let overlay_name = overlay.file_name().unwrap().to_str().unwrap();
+        for content in [
+            format!("include = [\"{}\"]\n", overlay_name),
+            "include = [\"missing.toml\"]\n".to_string(),
+            format!("include = \"{}\"\n", base_name),
+        ] {
+            std::fs::write(&overlay, &content).unwrap();
+            let mut bus = MockBus::new();
+            assert_eq!(run_args(&mut bus, &["load", overlay.to_str().unwrap()]), ExitCode::FAILURE, "{}", content);
+            assert_eq!(bus.regs, MockBus::new().regs);
+        }
+        std::fs::remove_file(&base).unwrap();
+        std::fs::remove_file(&overlay).unwrap();
+    }
+
+    #[test]
+    fn profiles() {
+        let dir = std::env::temp_dir().join(format!("sx1255-config-{}-profiles", std::process::id()));
+        let dir_arg = ["--profile-dir", dir.to_str().unwrap()];
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &[&["profile", "list"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        run_args(&mut bus, &["set", "rx_freq=435M", "tx_freq=438M", "rx_lna_gain=3"]);
+        assert_eq!(run_args(&mut bus, &[&["profile", "save", "m17"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        assert!(dir.join("m17.toml").is_file());
+        assert_eq!(run_args(&mut bus, &[&["profile", "save", "m17"][..], &dir_arg].concat()), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &[&["profile", "save", "m17", "--force"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["profile", "save", "../m17"][..], &dir_arg].concat()), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &[&["profile", "list"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["profile", "show", "m17"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["profile", "show", "test"][..], &dir_arg].concat()), ExitCode::FAILURE);
+
+        let mut applied = MockBus::new();
+        assert_eq!(run_args(&mut applied, &[&["profile", "apply", "m17"][..], &dir_arg].concat()), ExitCode::SUCCESS);
+        assert_eq!(applied.regs, bus.regs);
+        assert_eq!(run_args(&mut applied, &[&["profile", "apply", "test"][..], &dir_arg].concat()), ExitCode::FAILURE);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tune_channels() {
+        let file = temp_file("channels");
+        std::fs::write(&file, "\
+[[channel]]
+name = \"m17\"
+rx_freq = \"433.475M\"
+
+[[channel]]
+name = \"repeater\"
+rx_freq = 439500000
+tx_offset = -7600000
+rx_lna_gain = 1
+").unwrap();
+        let channels = ["--channels", file.to_str().unwrap()];
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &[&["channels", "list"][..], &channels].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["tune", "repeater"][..], &channels].concat()), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        // read back frequencies land on the nearest PLL step
+        assert_eq!(sx1255_info.rx_freq, 439500000);
+        assert!(sx1255_info.tx_freq.abs_diff(431900000) < 10);
+        assert_eq!(sx1255_info.rx_lna_gain, LnaGain::G1);
+
+        assert_eq!(run_args(&mut bus, &[&["tune", "m17"][..], &channels].concat()), ExitCode::SUCCESS);
+        assert!(state(&mut bus).tx_freq.abs_diff(433475000) < 10);
+        assert_eq!(run_args(&mut bus, &[&["tune", "aprs"][..], &channels].concat()), ExitCode::FAILURE);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(run_args(&mut bus, &[&["tune", "m17"][..], &channels].concat()), ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn duplex_follows_rx() {
+        let file = temp_file("duplex");
+        let duplex = ["--duplex-file", file.to_str().unwrap()];
+        let mut bus = MockBus::new();
+        assert_eq!(run_args(&mut bus, &[&["set", "rx_freq=439.5M", "tx_freq=438M"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["duplex", "minus", "7.6M"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert_eq!(Duplex::load(&file).unwrap().to_string(), "-7.6 MHz");
+        assert!(state(&mut bus).tx_freq.abs_diff(431900000) < 10);
+
+        // retuning Rx moves Tx along, unless Tx is set too
+        assert_eq!(run_args(&mut bus, &[&["set", "rx_freq", "439.0M"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert!(state(&mut bus).tx_freq.abs_diff(431400000) < 10);
+        assert_eq!(run_args(&mut bus, &[&["set", "rx_freq=439.5M", "tx_freq=435M"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert!(state(&mut bus).tx_freq.abs_diff(435000000) < 10);
+        assert_eq!(run_args(&mut bus, &[&["set", "rx_freq", "302M"][..], &duplex].concat()), ExitCode::FAILURE);
+
+        assert_eq!(run_args(&mut bus, &[&["duplex", "reverse"][..], &duplex].concat()), ExitCode::SUCCESS);
+        let sx1255_info = state(&mut bus);
+        assert!(sx1255_info.rx_freq.abs_diff(431900000) < 10);
+        assert!(sx1255_info.tx_freq.abs_diff(439500000) < 10);
+        assert_eq!(run_args(&mut bus, &[&["info"][..], &duplex].concat()), ExitCode::SUCCESS);
+
+        assert_eq!(run_args(&mut bus, &[&["duplex", "simplex"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["duplex", "reverse"][..], &duplex].concat()), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &[&["duplex", "off"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert_eq!(run_args(&mut bus, &[&["set", "rx_freq", "435M"][..], &duplex].concat()), ExitCode::SUCCESS);
+        assert!(state(&mut bus).tx_freq.abs_diff(431900000) < 10);
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn bus_error_fails() {
+        let mut bus = MockBus::new();
+        bus.fail = true;
+        assert_eq!(run_args(&mut bus, &["info"]), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &["set", "rx_enable", "true"]), ExitCode::FAILURE);
+        assert_eq!(run_args(&mut bus, &["watch", "-n", "1"]), ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn unverified_write_fails() {
+        let mut bus = MockBus::new();
+        bus.ignore_writes = true;
+        assert_eq!(run_args(&mut bus, &["set", "rx_lna_gain", "3"]), ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn load_rejects_invalid_file() {
+        let file = temp_file("load_rejects_invalid_file");
+        for content in ["r = 10\n", "iomap0 = 4\n", "rx_pga_bw = 4\n", "rx_enable = 1\n", "version = 17\n", "rx_gain = 1\n"] {
+            std::fs::write(&file, content).unwrap();
+            let mut bus = MockBus::new();
+            assert_eq!(run_args(&mut bus, &["load", file.to_str().unwrap()]), ExitCode::FAILURE, "{}", content);
+            assert_eq!(bus.regs, MockBus::new().regs);
+        }
+        std::fs::remove_file(&file).unwrap();
     }
 }
@@ -1,8 +1,13 @@
 use clap::Parser;
 use std::time::Instant;
 use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use alsa::{Direction, ValueOr};
 use alsa::pcm::{PCM, HwParams, Format, Access};
+use sx1255::Sx1255;
+use sx1255::hw::HwArgs;
+use sx1255::file::read_file;
 
 /// Takes IQ baseband samples from SX1255 vi the I2S audio device and puts them
 /// on a ZeroMQ pub socket
@@ -31,67 +36,114 @@ struct Args {
     /// print the rate at which we're publishing samples every 10 seconds
     #[arg(short, long)]
     print_sample_rate: bool,
+
+    /// load this sx1255-config file onto the SX1255 before streaming
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    hw: HwArgs,
 }
 
-fn main() {
+fn main() -> ExitCode {
     let args = Args::parse();
 
+    if let Some(config) = &args.config {
+        println!("Configuring SX1255 from {}", config.display());
+        let hw = match args.hw.resolve() {
+            Ok(hw) => hw,
+            Err(e) => {
+                eprintln!("Unable to load hardware profile: {}", e);
+                return ExitCode::FAILURE
+            },
+        };
+        let mut sx1255 = match Sx1255::open_hw(&hw) {
+            Ok(sx1255) => sx1255,
+            Err(e) => {
+                eprintln!("Unable to open SPI {}: {}", hw.spi_dev, e);
+                return ExitCode::FAILURE
+            },
+        };
+        let mut sx1255_info = match sx1255.read_state() {
+            Ok(sx1255_info) => sx1255_info,
+            Err(e) => {
+                eprintln!("Error reading SX1255 state: {}", e);
+                return ExitCode::FAILURE
+            },
+        };
+        match read_file(&mut sx1255_info, config) {
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("Error loading from {}: {}", config.display(), e);
+                return ExitCode::FAILURE
+            },
+        };
+        match sx1255.apply_state(sx1255_info) {
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("Error configuring SX1255: {}", e);
+                return ExitCode::FAILURE
+            },
+        };
+    }
+
     println!("Opening audio device");
     let pcm = match PCM::new(&args.device, Direction::Capture, false) {
         Ok(pcm) => pcm,
         Err(e) => {
-            println!("Error opening audio device {}: {}", &args.device, e);
-            return
+            eprintln!("Error opening audio device {}: {}", &args.device, e);
+            return ExitCode::FAILURE
         },
     };
     println!("Setting audio HW params");
     let hwp = match HwParams::any(&pcm) {
         Ok(hwp) => hwp,
         Err(e) => {
-            println!("Unable to get audio default HW params: {}", e);
-            return
+            eprintln!("Unable to get audio default HW params: {}", e);
+            return ExitCode::FAILURE
         },
     };
     match hwp.set_channels(2) {
         Ok(_) => {},
         Err(e) => {
-            println!("Unable to set audio channels to 2: {}", e);
-            return
+            eprintln!("Unable to set audio channels to 2: {}", e);
+            return ExitCode::FAILURE
         }
     }
     match hwp.set_rate(args.sample_rate, ValueOr::Nearest) {
         Ok(_) => {},
         Err(e) => {
-            println!("Unable to set audio sample rate to {}: {}", &args.sample_rate, e);
-            return
+            eprintln!("Unable to set audio sample rate to {}: {}", &args.sample_rate, e);
+            return ExitCode::FAILURE
         },
     }
     let format = match args.sample_format.as_str() {
         "S16_LE" => Format::s16(),
         "S32_LE" => Format::s32(),
         _ => {
-            println!("Invalid audio format");
-            return
+            eprintln!("Invalid audio format");
+            return ExitCode::FAILURE
         },
     };
     match hwp.set_format(format) {
         Ok(_) => {},
         Err(e) => {
-            println!("Unable to set audio format to {}: {}", format, e);
-            return
+            eprintln!("Unable to set audio format to {}: {}", format, e);
+            return ExitCode::FAILURE
         },
     }
     match hwp.set_access(Access::RWInterleaved) {
         Ok(_) => {},
         Err(e) => {
-            println!("Unable to set audio access mode to {:?}: {}", Access::RWInterleaved, e);
-            return
+            eprintln!("Unable to set audio access mode to {:?}: {}", Access::RWInterleaved, e);
+            return ExitCode::FAILURE
         },
     }
     match pcm.hw_params(&hwp) {
         Ok(_) => {},
         Err(e) => {
-            println!("Unable to set audio HW params: {}", e);
+            eprintln!("Unable to set audio HW params: {}", e);
+            return ExitCode::FAILURE
         },
     }
     let mut io = pcm.io_bytes();
@@ -101,15 +153,15 @@ fn main() {
     let publisher = match context.socket(zmq::PUB) {
         Ok(publisher) => publisher,
         Err(e) => {
-            println!("Error getting socket: {}", e);
-            return
+            eprintln!("Error getting socket: {}", e);
+            return ExitCode::FAILURE
         },
     };
     match publisher.bind(&args.endpoint) {
         Ok(_) => {},
         Err(e) => {
-            println!("Failed binding publisher: {}", e);
-            return
+            eprintln!("Failed binding publisher: {}", e);
+            return ExitCode::FAILURE
         },
     }
 
@@ -121,22 +173,22 @@ fn main() {
         bytes += match io.read(&mut buf) {
             Ok(bytes_read) => {
                 if bytes_read != args.msg_size {
-                    println!("Bytes read not {}: {}", args.msg_size, bytes_read);
-                    return
+                    eprintln!("Bytes read not {}: {}", args.msg_size, bytes_read);
+                    return ExitCode::FAILURE
                 }
                 bytes_read
             },
             Err(e) => {
-                println!("Error reading audio: {}", e);
-                return
+                eprintln!("Error reading audio: {}", e);
+                return ExitCode::FAILURE
             },
         };
 
         match publisher.send(buf, zmq::DONTWAIT) {
             Ok(_) => {},
             Err(e) => {
-                println!("Error sending: {}", e);
-                return
+                eprintln!("Error sending: {}", e);
+                return ExitCode::FAILURE
             },
         }
 
@@ -0,0 +1,123 @@
+use std::io;
+use spidev::{Spidev, SpidevTransfer};
+
+use crate::regs::{NUM_REGS, REG_VERSION, REG_STAT};
+
+/// A bus that exchanges SPI frames with an SX1255
+///
+/// A frame is an address byte (MSB set for a write) followed by one or more
+/// data bytes. The chip increments the address after each data byte, and
+/// `rx_buf` receives the register contents before any write.
+pub trait RegisterBus {
+    fn transfer(&mut self, tx_buf: &[u8], rx_buf: &mut [u8]) -> io::Result<()>;
+}
+
+impl RegisterBus for Spidev {
+    fn transfer(&mut self, tx_buf: &[u8], rx_buf: &mut [u8]) -> io::Result<()> {
+        let mut transfer = SpidevTransfer::read_write(tx_buf, rx_buf);
+        Spidev::transfer(self, &mut transfer)
+    }
+}
+
+impl<B: RegisterBus + ?Sized> RegisterBus for &mut B {
+    fn transfer(&mut self, tx_buf: &[u8], rx_buf: &mut [u8]) -> io::Result<()> {
+        (**self).transfer(tx_buf, rx_buf)
+    }
+}
+
+/// In-memory model of the SX1255 register file for testing without hardware
+///
+/// Writes to the read-only VERSION and STAT registers are ignored, as are
+/// accesses past the end of the register file. Setting `fail` makes every
+/// transfer return an error, as an unplugged device would, and setting
+/// `ignore_writes` makes the chip silently drop writes like a loose ribbon.
+#[derive(Debug, Clone)]
+pub struct MockBus {
+    pub regs: [u8; NUM_REGS],
+    pub fail: bool,
+    pub ignore_writes: bool,
+}
+
+// register values after reset, see SX1255Info::default()
+static MOCK_RESET_REGS: [u8; NUM_REGS] = [
+    0x01,             // MODE
+    0xC0, 0xE3, 0x8D, // FRF_RX
+    0xC0, 0xE3, 0x8D, // FRF_TX
+    0x11,             // VERSION
+    0x2C, 0x1F, 0x60, 0x02, // TXFE1-4
+    0x3F, 0xFD, 0x06, // RXFE1-3
+    0x00,             // IO_MAP
+    0x02,             // CK_SEL
+    0x04,             // STAT
+    0x00,             // IISM
+    0x00,             // DIG_BRIDGE
+];
+
+impl MockBus {
+    /// Creates a mock in its power on state
+    pub fn new() -> MockBus {
+        MockBus { regs: MOCK_RESET_REGS, fail: false, ignore_writes: false }
+    }
+}
+
+impl Default for MockBus {
+    fn default() -> MockBus {
+        MockBus::new()
+    }
+}
+
+impl RegisterBus for MockBus {
+    fn transfer(&mut self, tx_buf: &[u8], rx_buf: &mut [u8]) -> io::Result<()> {
+        if self.fail {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "mock bus failure"));
+        }
+        if tx_buf.is_empty() || tx_buf.len() != rx_buf.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "bad SPI frame"));
+        }
+        let write = (tx_buf[0] & 0b10000000) != 0;
+        let start = (tx_buf[0] & 0b01111111) as usize;
+        rx_buf[0] = 0;
+        for i in 1..tx_buf.len() {
+            let addr = start + i - 1;
+            rx_buf[i] = if addr < NUM_REGS { self.regs[addr] } else { 0 };
+            let read_only = addr == REG_VERSION as usize || addr == REG_STAT as usize;
+            if write && addr < NUM_REGS && !read_only && !self.ignore_writes {
+                self.regs[addr] = tx_buf[i];
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::{sx1255_readreg, sx1255_writereg};
+    use crate::regs::{REG_MODE, REG_TXFE1};
+
+    #[test]
+    fn write_returns_previous_value() {
+        let mut bus = MockBus::new();
+        assert_eq!(sx1255_writereg(&mut bus, REG_MODE, 0x03).unwrap(), 0x01);
+        assert_eq!(sx1255_readreg(&mut bus, REG_MODE).unwrap(), 0x03);
+    }
+
+    #[test]
+    fn read_only_registers_ignore_writes() {
+        let mut bus = MockBus::new();
+        sx1255_writereg(&mut bus, REG_VERSION, 0xFF).unwrap();
+        sx1255_writereg(&mut bus, REG_STAT, 0xFF).unwrap();
+        assert_eq!(sx1255_readreg(&mut bus, REG_VERSION).unwrap(), 0x11);
+        assert_eq!(sx1255_readreg(&mut bus, REG_STAT).unwrap(), 0x04);
+    }
+
+    #[test]
+    fn burst_increments_address() {
+        let mut bus = MockBus::new();
+        let mut rx_buf = [0_u8; 4];
+        bus.transfer(&[REG_TXFE1 | 0b10000000, 0x11, 0x22, 0x33], &mut rx_buf).unwrap();
+        assert_eq!(rx_buf[1..], [0x2C, 0x1F, 0x60]);
+        bus.transfer(&[REG_TXFE1, 0, 0, 0], &mut rx_buf).unwrap();
+        assert_eq!(rx_buf[1..], [0x11, 0x22, 0x33]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use crate::error::Sx1255Error;
+use crate::fields::{self, Field};
+use crate::file::parse_setting;
+use crate::info::SX1255Info;
+use crate::user_config_dir;
+
+/// Channels shared by every user of the system
+pub static SYSTEM_CHANNELS_FILE: &str = "/etc/sx1255/channels.toml";
+
+/// Per-user channel table, `~/.config/sx1255/channels.toml`
+pub fn user_channels_file() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("channels.toml"))
+}
+
+/// The channel table to use, the user's if there is one
+pub fn default_channels_file() -> PathBuf {
+    user_channels_file()
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_CHANNELS_FILE))
+}
+
+/// A named pair of carrier frequencies and the settings that go with them
+#[derive(Clone)]
+pub struct Channel {
+    pub name: String,
+    /// Rx carrier frequency in Hz
+    pub rx_freq: u32,
+    /// Tx carrier frequency in Hz
+    pub tx_freq: u32,
+    /// Other settings to apply, like bandwidths and gains
+    pub settings: Vec<(&'static Field, u32)>,
+}
+
+impl Channel {
+    /// Stores the channel's frequencies and settings in `sx1255_info`
+    pub fn apply(&self, sx1255_info: &mut SX1255Info) -> Result<(), Sx1255Error> {
+        let rx_freq = fields::find("rx_freq").expect("rx_freq exists");
+        let tx_freq = fields::find("tx_freq").expect("tx_freq exists");
+        rx_freq.apply(sx1255_info, self.rx_freq)?;
+        tx_freq.apply(sx1255_info, self.tx_freq)?;
+        for (field, value) in &self.settings {
+            field.apply(sx1255_info, *value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Channels read from a TOML file of `[[channel]]` tables
+///
+/// Each needs a `name` and `rx_freq`. `tx_freq` defaults to `rx_freq`, or
+/// `tx_offset` gives it relative to `rx_freq`. Any other key is a setting
+/// like in a saved file. Frequencies can be written as `"435.125M"`.
+#[derive(Clone, Default)]
+pub struct ChannelTable {
+    pub channels: Vec<Channel>,
+}
+
+impl ChannelTable {
+    /// Reads and checks a channel table
+    pub fn load(path: &Path) -> Result<ChannelTable, Sx1255Error> {
+        let error = |msg: String| Sx1255Error::Config(format!("{}: {}", path.display(), msg));
+        let content = read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let mut table: toml::Table = toml::from_str(&content).map_err(|e| error(e.message().to_string()))?;
+        let entries = match table.remove("channel") {
+            Some(toml::Value::Array(entries)) => entries,
+            Some(_) => return Err(error("channel must be a list of [[channel]] tables".to_string())),
+            None => Vec::new(),
+        };
+        if let Some(key) = table.keys().next() {
+            return Err(error(format!("unknown key {}", key)));
+        }
+
+        let mut channels: Vec<Channel> = Vec::new();
+        for (i, entry) in entries.into_iter().enumerate() {
+            let channel = match entry {
+                toml::Value::Table(entry) => parse_channel(entry),
+                _ => Err("must be a table".to_string()),
+            }.map_err(|e| error(format!("channel {}: {}", i + 1, e)))?;
+            if channels.iter().any(|c| c.name == channel.name) {
+                return Err(error(format!("channel {} is listed twice", channel.name)));
+            }
+            channels.push(channel);
+        }
+        Ok(ChannelTable { channels })
+    }
+
+    /// Looks a channel up by name
+    pub fn find(&self, name: &str) -> Option<&Channel> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+}
+
+fn parse_channel(mut entry: toml::Table) -> Result<Channel, String> {
+    let name = match entry.remove("name") {
+        Some(toml::Value::String(name)) if !name.is_empty() => name,
+        Some(_) => return Err("name must be a string".to_string()),
+        None => return Err("name is missing".to_string()),
+    };
+    let in_channel = |e: String| format!("{}: {}", name, e);
+    let rx_freq = match entry.remove("rx_freq") {
+        Some(value) => parse_setting("rx_freq", &value).map_err(in_channel)?.1,
+        None => return Err(in_channel("rx_freq is missing".to_string())),
+    };
+    let tx_freq = match (entry.remove("tx_freq"), entry.remove("tx_offset")) {
+        (Some(_), Some(_)) => return Err(in_channel("give tx_freq or tx_offset, not both".to_string())),
+        (Some(value), None) => parse_setting("tx_freq", &value).map_err(in_channel)?.1,
+        (None, Some(offset)) => {
+            let offset = match offset {
+                toml::Value::Integer(offset) => Some(offset as f64),
+                toml::Value::String(offset) => fields::parse_si(&offset, "Hz"),
+                _ => None,
+            }.ok_or_else(|| in_channel("tx_offset must be a number of Hz".to_string()))?;
+            let tx_freq = (rx_freq as f64 + offset).round();
+            if !(0.0..=u32::MAX as f64).contains(&tx_freq) {
+                return Err(in_channel("tx_offset is out of range".to_string()));
+            }
+            tx_freq as u32
+        },
+        (None, None) => rx_freq,
+    };
+    let tx = fields::find("tx_freq").expect("tx_freq exists");
+    tx.check(tx_freq).map_err(|e| in_channel(e.to_string()))?;
+
+    let settings = entry.iter()
+        .map(|(key, value)| parse_setting(key, value).map_err(in_channel))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Channel { name, rx_freq, tx_freq, settings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{remove_file, write};
+
+    #[test]
+    fn loads_channels() {
+        let path = std::env::temp_dir().join(format!("sx1255-channels-{}.toml", std::process::id()));
+        write(&path, "\
+[[channel]]
+name = \"simplex\"
+rx_freq = 433475000
+
+[[channel]]
+name = \"repeater\"
+rx_freq = \"439.5M\"
+tx_offset = \"-7.6M\"
+rx_lna_gain = 1
+rx_pga_bw = \"500k\"
+").unwrap();
+        let table = ChannelTable::load(&path).unwrap();
+        let simplex = table.find("simplex").unwrap();
+        assert_eq!((simplex.rx_freq, simplex.tx_freq), (433475000, 433475000));
+        let repeater = table.find("repeater").unwrap();
+        assert_eq!((repeater.rx_freq, repeater.tx_freq), (439500000, 431900000));
+        let mut sx1255_info = SX1255Info::default();
+        repeater.apply(&mut sx1255_info).unwrap();
+        assert_eq!(sx1255_info.tx_freq, 431900000);
+        assert_eq!(sx1255_info.rx_pga_bw, crate::regs::PgaBw::Bw500k);
+        assert!(table.find("m17").is_none());
+
+        for content in [
+            "[[channel]]\nrx_freq = 433475000\n",
+            "[[channel]]\nname = \"a\"\n",
+            "[[channel]]\nname = \"a\"\nrx_freq = 433475000\ntx_freq = 433475000\ntx_offset = 0\n",
+            "[[channel]]\nname = \"a\"\nrx_freq = 433475000\ntx_offset = \"-200M\"\n",
+            "[[channel]]\nname = \"a\"\nrx_freq = 433475000\nrx_gain = 1\n",
+            "[[channel]]\nname = \"a\"\nrx_freq = 433475000\n[[channel]]\nname = \"a\"\nrx_freq = 433475000\n",
+            "channels = []\n",
+        ] {
+            write(&path, content).unwrap();
+            assert!(ChannelTable::load(&path).is_err(), "{}", content);
+        }
+        remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Sx1255Error;
+use crate::fields;
+use crate::info::SX1255Info;
+use crate::user_config_dir;
+
+/// Where the duplex setting is kept, `~/.config/sx1255/duplex.toml`
+pub fn user_duplex_file() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("duplex.toml"))
+}
+
+/// Which way the Tx frequency sits from the Rx frequency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shift {
+    /// Tx is tuned on its own
+    #[default]
+    Off,
+    /// Tx on the Rx frequency
+    Simplex,
+    /// Tx above Rx by the offset
+    Plus,
+    /// Tx below Rx by the offset
+    Minus,
+}
+
+/// How `tx_freq` follows `rx_freq`, e.g. for working through a repeater
+///
+/// The chip has nowhere to keep this, so it is saved in its own file and
+/// applied whenever `rx_freq` is retuned. `reverse` swaps the direction of a
+/// plus or minus split, for listening on the repeater input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Duplex {
+    pub shift: Shift,
+    /// Distance between Rx and Tx in Hz for a plus or minus split
+    pub offset: u32,
+    pub reverse: bool,
+}
+
+impl Duplex {
+    /// Reads a duplex setting, a missing file means off
+    pub fn load(path: &Path) -> Result<Duplex, Sx1255Error> {
+        if !path.exists() {
+            return Ok(Duplex::default());
+        }
+        let error = |msg: String| Sx1255Error::Config(format!("{}: {}", path.display(), msg));
+        let content = read_to_string(path).map_err(|e| error(e.to_string()))?;
+        toml::from_str(&content).map_err(|e| error(e.message().to_string()))
+    }
+
+    /// Writes the setting to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), Sx1255Error> {
+        let error = |msg: String| Sx1255Error::Config(format!("{}: {}", path.display(), msg));
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).map_err(|e| error(e.to_string()))?;
+        }
+        let content = toml::to_string(self).map_err(|e| error(e.to_string()))?;
+        write(path, content).map_err(|e| error(e.to_string()))
+    }
+
+    /// Tx minus Rx in Hz, `None` when Tx is tuned on its own
+    pub fn split(&self) -> Option<i64> {
+        let split = match self.shift {
+            Shift::Off => return None,
+            Shift::Simplex => 0,
+            Shift::Plus => self.offset as i64,
+            Shift::Minus => -(self.offset as i64),
+        };
+        Some(if self.reverse { -split } else { split })
+    }
+
+    /// The Tx frequency that goes with `rx_freq`, `None` when Tx is tuned on its own
+    pub fn tx_freq(&self, rx_freq: u32) -> Result<Option<u32>, Sx1255Error> {
+        let Some(split) = self.split() else {
+            return Ok(None);
+        };
+        let field = fields::find("tx_freq").expect("tx_freq exists");
+        let tx_freq = u32::try_from(rx_freq as i64 + split).map_err(|_| Sx1255Error::InvalidValue(
+            format!("{} Hz {} is out of range", rx_freq, self)))?;
+        field.check(tx_freq)?;
+        Ok(Some(tx_freq))
+    }
+
+    /// Retunes `tx_freq` in `sx1255_info` to follow its `rx_freq`
+    pub fn apply(&self, sx1255_info: &mut SX1255Info) -> Result<(), Sx1255Error> {
+        if let Some(tx_freq) = self.tx_freq(sx1255_info.rx_freq)? {
+            sx1255_info.tx_freq = tx_freq;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Duplex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.split() {
+            None => write!(f, "off")?,
+            Some(0) => write!(f, "simplex")?,
+            Some(split) => write!(f, "{:+} MHz", split as f64 / 1e6)?,
+        }
+        if self.reverse {
+            write!(f, " (reverse)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_rx_freq() {
+        let mut duplex = Duplex::default();
+        assert_eq!(duplex.tx_freq(439500000).unwrap(), None);
+        assert_eq!(duplex.to_string(), "off");
+
+        duplex.shift = Shift::Minus;
+        duplex.offset = 7600000;
+        assert_eq!(duplex.tx_freq(439500000).unwrap(), Some(431900000));
+        assert_eq!(duplex.to_string(), "-7.6 MHz");
+        duplex.reverse = true;
+        assert_eq!(duplex.tx_freq(431900000).unwrap(), Some(439500000));
+        assert_eq!(duplex.to_string(), "+7.6 MHz (reverse)");
+        assert!(duplex.tx_freq(499000000).is_err());
+
+        let mut sx1255_info = SX1255Info { rx_freq: 435000000, ..SX1255Info::default() };
+        Duplex { shift: Shift::Simplex, ..Duplex::default() }.apply(&mut sx1255_info).unwrap();
+        assert_eq!(sx1255_info.tx_freq, 435000000);
+
+        let path = std::env::temp_dir().join(format!("sx1255-duplex-{}.toml", std::process::id()));
+        assert_eq!(Duplex::load(&path).unwrap(), Duplex::default());
+        duplex.save(&path).unwrap();
+        assert_eq!(Duplex::load(&path).unwrap(), duplex);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use std::{error, fmt, io};
+
+/// A field whose value read back differently than it was written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Errors returned by the SX1255 driver
+#[derive(Debug)]
+pub enum Sx1255Error {
+    /// The SPI (or other register bus) transfer failed
+    Bus(io::Error),
+    /// A value can't be represented by the register field it is meant for
+    InvalidValue(String),
+    /// Registers read back differently than they were written
+    Verify(Vec<Mismatch>),
+    /// The GPIO reset line couldn't be driven
+    Gpio(gpio_cdev::Error),
+    /// A configuration file couldn't be read or is invalid
+    Config(String),
+    /// A PLL didn't lock in time
+    Lock(String),
+    /// The PA driver would be enabled outside the allowed Tx bands
+    Interlock(String),
+}
+
+impl fmt::Display for Sx1255Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Sx1255Error::Bus(e) => write!(f, "bus error: {}", e),
+            Sx1255Error::InvalidValue(msg) => write!(f, "invalid value: {}", msg),
+            Sx1255Error::Verify(mismatches) => {
+                write!(f, "verification failed:")?;
+                for m in mismatches {
+                    write!(f, "\n  {}: wrote {}, read back {}", m.field, m.expected, m.actual)?;
+                }
+                Ok(())
+            },
+            Sx1255Error::Gpio(e) => write!(f, "GPIO error: {}", e),
+            Sx1255Error::Config(msg) => write!(f, "configuration error: {}", msg),
+            Sx1255Error::Lock(msg) => write!(f, "PLL lock failed: {}", msg),
+            Sx1255Error::Interlock(msg) => write!(f, "Tx interlock: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Sx1255Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Sx1255Error::Bus(e) => Some(e),
+            Sx1255Error::Gpio(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Sx1255Error {
+    fn from(e: io::Error) -> Sx1255Error {
+        Sx1255Error::Bus(e)
+    }
+}
+
+impl From<gpio_cdev::Error> for Sx1255Error {
+    fn from(e: gpio_cdev::Error) -> Sx1255Error {
+        Sx1255Error::Gpio(e)
+    }
+}
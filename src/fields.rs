@@ -0,0 +1,629 @@
+use crate::error::Sx1255Error;
+use crate::info::{SX1255Info, VALID_R_VALUES};
+use crate::opts::OPTS;
+use crate::regs::*;
+
+/// How a field's value is written, checked and shown
+#[derive(Debug, Clone, Copy)]
+pub enum Kind {
+    /// `true` or `false`
+    Bool,
+    /// A plain number
+    Number,
+    /// A register code, described by the value at its index
+    Code(&'static [&'static str]),
+    /// A number shown in hex
+    Hex,
+    /// A decimation/interpolation factor, see [`VALID_R_VALUES`]
+    Factor,
+}
+
+/// Physical value of each code of a field, so `set` can take engineering units
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+    /// SI unit of the values
+    pub units: &'static str,
+    /// Value of each code, NaN for codes that don't have one
+    pub values: &'static [f64],
+}
+
+/// Everything there is to know about one setting of the SX1255
+///
+/// [`FIELDS`] is the single source for `set` subcommands, file validation,
+/// saved files and the `info` report, so adding a field there is enough to
+/// expose it everywhere.
+pub struct Field {
+    /// Key in files and name of the `set` subcommand
+    pub name: &'static str,
+    /// Older `set` subcommand names that still work
+    pub aliases: &'static [&'static str],
+    /// Label in the `info` report
+    pub label: &'static str,
+    /// `info` report section
+    pub section: &'static str,
+    /// One line description, used as the `set` subcommand help
+    pub help: &'static str,
+    /// How to work out the physical value, if the codes aren't listed
+    pub detail: &'static str,
+    /// Register (or first register) holding the field
+    pub reg: u8,
+    /// Lowest bit of the field
+    pub shift: u8,
+    /// Number of bits in the field
+    pub width: u8,
+    /// Smallest valid value
+    pub min: u32,
+    /// Largest valid value
+    pub max: u32,
+    /// Units of the value, if it isn't a code
+    pub units: &'static str,
+    pub kind: Kind,
+    /// What the codes mean in `units`, for fields that aren't a plain number
+    pub scale: Option<Scale>,
+    /// Read only fields are reported but never written or saved
+    pub writable: bool,
+    pub get: fn(&SX1255Info) -> u32,
+    pub set: fn(&mut SX1255Info, u32) -> Result<(), Sx1255Error>,
+}
+
+const GENERAL: &str = "General Registers";
+const TX: &str = "Transmitter Front-End Configuration Registers";
+const RX: &str = "Receiver Front-End Configuration Registers";
+const IO: &str = "IRC and PIN Mapping Registers";
+const ADDITIONAL: &str = "Additional Parameter Configuration Registers";
+
+// Rx and Tx PLL bandwidths in Hz
+const PLL_BW: [f64; 4] = [75e3, 150e3, 225e3, 300e3];
+// BW3dB = 17.15 / (41 - code) MHz
+const TX_FILTER_BW: [f64; 16] = {
+    let mut bw = [0.0; 16];
+    let mut code = 0;
+    while code < bw.len() {
+        bw[code] = 17.15e6 / (41 - code) as f64;
+        code += 1;
+    }
+    bw
+};
+
+/// Every field of [`SX1255Info`], in register order
+pub static FIELDS: [Field; 41] = [
+    Field {
+        name: "driver_enable", aliases: &[], label: "PA driver enabled", section: GENERAL,
+        help: "Enables the PA driver", detail: "",
+        reg: REG_MODE, shift: 3, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.driver_enable as u32,
+        set: |i, v| { i.driver_enable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "tx_enable", aliases: &[], label: "Tx enabled", section: GENERAL,
+        help: "Enables the complete Tx part of the front-end (except the PA)", detail: "",
+        reg: REG_MODE, shift: 2, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.tx_enable as u32,
+        set: |i, v| { i.tx_enable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "rx_enable", aliases: &[], label: "Rx enabled", section: GENERAL,
+        help: "Enables the complete Rx part of the front-end", detail: "",
+        reg: REG_MODE, shift: 1, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.rx_enable as u32,
+        set: |i, v| { i.rx_enable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "ref_enable", aliases: &[], label: "PDS and oscillator enabled", section: GENERAL,
+        help: "Enables the PDS and the oscillator", detail: "",
+        reg: REG_MODE, shift: 0, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.ref_enable as u32,
+        set: |i, v| { i.ref_enable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "rx_freq", aliases: &[], label: "Rx carrier frequency", section: GENERAL,
+        help: "Sets the Rx frequency", detail: "",
+        reg: REG_FRFH_RX, shift: 0, width: 24, min: 300000000, max: 500000000, units: "Hz", kind: Kind::Number, writable: true,
+        scale: None,
+        get: |i| i.rx_freq,
+        set: |i, v| { i.rx_freq = v; Ok(()) },
+    },
+    Field {
+        name: "tx_freq", aliases: &[], label: "Tx carrier frequency", section: GENERAL,
+        help: "Sets the Tx frequency", detail: "",
+        reg: REG_FRFH_TX, shift: 0, width: 24, min: 300000000, max: 500000000, units: "Hz", kind: Kind::Number, writable: true,
+        scale: None,
+        get: |i| i.tx_freq,
+        set: |i, v| { i.tx_freq = v; Ok(()) },
+    },
+    Field {
+        name: "version", aliases: &[], label: "Version code", section: GENERAL,
+        help: "Version code of the chip", detail: "",
+        reg: REG_VERSION, shift: 0, width: 8, min: 0, max: 255, units: "", kind: Kind::Hex, writable: false,
+        scale: None,
+        get: |i| i.version as u32,
+        set: |i, v| { i.version = v as u8; Ok(()) },
+    },
+    Field {
+        name: "tx_dac_gain", aliases: &[], label: "Tx DAC gain", section: TX,
+        help: "Sets the Tx DAC gain", detail: "",
+        reg: REG_TXFE1, shift: 4, width: 3, min: 0, max: 7, units: "", kind: Kind::Code(&OPTS.tx_dac_gain), writable: true,
+        scale: Some(Scale { units: "dB", values: &[-9.0, -6.0, -3.0, 0.0] }),
+        get: |i| i.tx_dac_gain as u32,
+        set: |i, v| { i.tx_dac_gain = DacGain::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "tx_mixer_gain", aliases: &[], label: "Tx mixer gain", section: TX,
+        help: "Sets the Tx mixer gain", detail: "Gain ~ -37.5 + 2 x tx_mixer_gain in dB",
+        reg: REG_TXFE1, shift: 0, width: 4, min: 0, max: 15, units: "", kind: Kind::Code(&OPTS.tx_mixer_gain), writable: true,
+        scale: Some(Scale { units: "dB", values: &[
+            -37.5, -35.5, -33.5, -31.5, -29.5, -27.5, -25.5, -23.5,
+            -21.5, -19.5, -17.5, -15.5, -13.5, -11.5, -9.5, -7.5,
+        ] }),
+        get: |i| i.tx_mixer_gain as u32,
+        set: |i, v| { i.tx_mixer_gain = v as u8; Ok(()) },
+    },
+    Field {
+        name: "tx_mixer_tank_cap", aliases: &[], label: "Tx mixer tank capacitance", section: TX,
+        help: "Sets the capacitance in parallel with the mixer tank", detail: "Cap = 128 * tx_mixer_tank_cap [fF]",
+        reg: REG_TXFE2, shift: 3, width: 3, min: 0, max: 7, units: "", kind: Kind::Code(&OPTS.tx_mixer_tank_cap), writable: true,
+        scale: Some(Scale { units: "F", values: &[0.0, 128e-15, 256e-15, 384e-15, 512e-15, 640e-15, 768e-15, 896e-15] }),
+        get: |i| i.tx_mixer_tank_cap as u32,
+        set: |i, v| { i.tx_mixer_tank_cap = v as u8; Ok(()) },
+    },
+    Field {
+        name: "tx_mixer_tank_res", aliases: &[], label: "Tx mixer tank resistance", section: TX,
+        help: "Sets the resistance in parallel with the mixer tank", detail: "",
+        reg: REG_TXFE2, shift: 0, width: 3, min: 0, max: 7, units: "", kind: Kind::Code(&OPTS.tx_mixer_tank_res), writable: true,
+        scale: Some(Scale { units: "Ω", values: &[950.0, 1110.0, 1320.0, 1650.0, 2180.0, 3240.0, 6000.0, 64000.0] }),
+        get: |i| i.tx_mixer_tank_res as u32,
+        set: |i, v| { i.tx_mixer_tank_res = TankRes::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "tx_pll_bw", aliases: &[], label: "Tx PLL bandwidth", section: TX,
+        help: "Sets the Tx PLL bandwidth", detail: "PLL BW = (tx_pll_bw + 1) * 75 KHz",
+        reg: REG_TXFE3, shift: 5, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.tx_pll_bw), writable: true,
+        scale: Some(Scale { units: "Hz", values: &PLL_BW }),
+        get: |i| i.tx_pll_bw as u32,
+        set: |i, v| { i.tx_pll_bw = PllBw::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "tx_filter_bw", aliases: &[], label: "Tx filter bandwidth", section: TX,
+        help: "Sets the Tx analog filter bandwidth DSB", detail: "BW3dB = 17.15 / (41 - tx_filter_bw) MHz",
+        reg: REG_TXFE3, shift: 0, width: 4, min: 0, max: 15, units: "", kind: Kind::Code(&OPTS.tx_filter_bw), writable: true,
+        scale: Some(Scale { units: "Hz", values: &TX_FILTER_BW }),
+        get: |i| i.tx_filter_bw as u32,
+        set: |i, v| { i.tx_filter_bw = v as u8; Ok(()) },
+    },
+    Field {
+        name: "tx_dac_bw", aliases: &[], label: "Tx DAC bandwidth", section: TX,
+        help: "Sets the number of taps of the Tx FIR-DAC", detail: "Actual number of taps = 24 + 8 * tx_dac_bw, max = 64",
+        reg: REG_TXFE4, shift: 0, width: 3, min: 0, max: 5, units: "", kind: Kind::Code(&OPTS.tx_dac_bw), writable: true,
+        scale: None,
+        get: |i| i.tx_dac_bw as u32,
+        set: |i, v| { i.tx_dac_bw = v as u8; Ok(()) },
+    },
+    Field {
+        name: "rx_lna_gain", aliases: &[], label: "Rx LNA gain", section: RX,
+        help: "Sets the Rx LNA gain", detail: "",
+        reg: REG_RXFE1, shift: 5, width: 3, min: 0, max: 7, units: "", kind: Kind::Code(&OPTS.rx_lna_gain), writable: true,
+        scale: Some(Scale { units: "dB", values: &[f64::NAN, 0.0, -6.0, -12.0, -24.0, -36.0, -48.0] }),
+        get: |i| i.rx_lna_gain as u32,
+        set: |i, v| { i.rx_lna_gain = LnaGain::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "rx_pga_gain", aliases: &[], label: "Rx PGA gain", section: RX,
+        help: "Sets the Rx PGA gain", detail: "Gain = lowest gain + 2 dB * rx_pga_gain",
+        reg: REG_RXFE1, shift: 1, width: 4, min: 0, max: 15, units: "", kind: Kind::Code(&OPTS.rx_pga_gain), writable: true,
+        scale: Some(Scale { units: "dB", values: &[
+            0.0, 2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0,
+            16.0, 18.0, 20.0, 22.0, 24.0, 26.0, 28.0, 30.0,
+        ] }),
+        get: |i| i.rx_pga_gain as u32,
+        set: |i, v| { i.rx_pga_gain = v as u8; Ok(()) },
+    },
+    Field {
+        name: "rx_zin_200", aliases: &["rx_zin200"], label: "Input Impedance", section: RX,
+        help: "Sets the Rx input impedance", detail: "",
+        reg: REG_RXFE1, shift: 0, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.rx_zin_200), writable: true,
+        scale: Some(Scale { units: "Ω", values: &[50.0, 200.0] }),
+        get: |i| i.rx_zin_200 as u32,
+        set: |i, v| { i.rx_zin_200 = RxZin::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "rx_adc_bw", aliases: &[], label: "Rx ΣΔ ADC BW configuration", section: RX,
+        help: "Sets the Rx ΣΔ ADC bandwidth", detail: "",
+        reg: REG_RXFE2, shift: 5, width: 3, min: 0, max: 7, units: "", kind: Kind::Code(&OPTS.rx_adc_bw), writable: true,
+        scale: None,
+        get: |i| i.rx_adc_bw as u32,
+        set: |i, v| { i.rx_adc_bw = v as u8; Ok(()) },
+    },
+    Field {
+        name: "rx_adc_trim", aliases: &[], label: "Rx ADC trim 36 MHz ref xtal", section: RX,
+        help: "Sets the Rx ADC trim for 36 MHz reference crystal", detail: "",
+        reg: REG_RXFE2, shift: 2, width: 3, min: 0, max: 7, units: "", kind: Kind::Number, writable: true,
+        scale: None,
+        get: |i| i.rx_adc_trim as u32,
+        set: |i, v| { i.rx_adc_trim = v as u8; Ok(()) },
+    },
+    Field {
+        name: "rx_pga_bw", aliases: &[], label: "Rx analog roofing filter", section: RX,
+        help: "Sets the Rx analog roofing filter", detail: "",
+        reg: REG_RXFE2, shift: 0, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.rx_pga_bw), writable: true,
+        scale: Some(Scale { units: "Hz", values: &[1500e3, 1000e3, 750e3, 500e3] }),
+        get: |i| i.rx_pga_bw as u32,
+        set: |i, v| { i.rx_pga_bw = PgaBw::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "rx_pll_bw", aliases: &[], label: "Rx PLL bandwidth", section: RX,
+        help: "Sets the Rx PLL bandwidth", detail: "PLL BW = (rx_pll_bw + 1) * 75 KHz",
+        reg: REG_RXFE3, shift: 1, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.rx_pll_bw), writable: true,
+        scale: Some(Scale { units: "Hz", values: &PLL_BW }),
+        get: |i| i.rx_pll_bw as u32,
+        set: |i, v| { i.rx_pll_bw = PllBw::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "rx_adc_temp", aliases: &[], label: "Rx ADC temp measure mode", section: RX,
+        help: "Puts the Rx ADC into temperature measurement mode", detail: "",
+        reg: REG_RXFE3, shift: 0, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.rx_adc_temp as u32,
+        set: |i, v| { i.rx_adc_temp = v != 0; Ok(()) },
+    },
+    Field {
+        name: "iomap0", aliases: &["io_map0"], label: "Mapping of DIO(0)", section: IO,
+        help: "Sets the mapping of DIO(0)", detail: "",
+        reg: REG_IO_MAP, shift: 6, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.iomap0), writable: true,
+        scale: None,
+        get: |i| i.iomap0 as u32,
+        set: |i, v| { i.iomap0 = v as u8; Ok(()) },
+    },
+    Field {
+        name: "iomap1", aliases: &["io_map1"], label: "Mapping of DIO(1)", section: IO,
+        help: "Sets the mapping of DIO(1)", detail: "",
+        reg: REG_IO_MAP, shift: 4, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.iomap1), writable: true,
+        scale: None,
+        get: |i| i.iomap1 as u32,
+        set: |i, v| { i.iomap1 = v as u8; Ok(()) },
+    },
+    Field {
+        name: "iomap2", aliases: &["io_map2"], label: "Mapping of DIO(2)", section: IO,
+        help: "Sets the mapping of DIO(2)", detail: "",
+        reg: REG_IO_MAP, shift: 2, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.iomap2), writable: true,
+        scale: None,
+        get: |i| i.iomap2 as u32,
+        set: |i, v| { i.iomap2 = v as u8; Ok(()) },
+    },
+    Field {
+        name: "iomap3", aliases: &["io_map3"], label: "Mapping of DIO(3)", section: IO,
+        help: "Sets the mapping of DIO(3)", detail: "",
+        reg: REG_IO_MAP, shift: 0, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.iomap3), writable: true,
+        scale: None,
+        get: |i| i.iomap3 as u32,
+        set: |i, v| { i.iomap3 = v as u8; Ok(()) },
+    },
+    Field {
+        name: "dig_loopback_en", aliases: &[], label: "Digital loopback enabled", section: ADDITIONAL,
+        help: "Enables the digital loop back mode of the frontend", detail: "",
+        reg: REG_CK_SEL, shift: 3, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.dig_loopback_en as u32,
+        set: |i, v| { i.dig_loopback_en = v != 0; Ok(()) },
+    },
+    Field {
+        name: "rf_loopback_en", aliases: &[], label: "RF loopback enabled", section: ADDITIONAL,
+        help: "Enables the RF loop back mode of the frontend", detail: "",
+        reg: REG_CK_SEL, shift: 2, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.rf_loopback_en as u32,
+        set: |i, v| { i.rf_loopback_en = v != 0; Ok(()) },
+    },
+    Field {
+        name: "ckout_enable", aliases: &[], label: "Clock out enabled", section: ADDITIONAL,
+        help: "Enables the clock output on pad CLK_OUT", detail: "",
+        reg: REG_CK_SEL, shift: 1, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.ckout_enable), writable: true,
+        scale: None,
+        get: |i| i.ckout_enable as u32,
+        set: |i, v| { i.ckout_enable = ClkOut::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "ck_select_tx_dac", aliases: &[], label: "Clock select for Tx DAC", section: ADDITIONAL,
+        help: "Selects the clock used by the Tx DAC", detail: "",
+        reg: REG_CK_SEL, shift: 0, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.ck_select_tx_dac), writable: true,
+        scale: None,
+        get: |i| i.ck_select_tx_dac as u32,
+        set: |i, v| { i.ck_select_tx_dac = DacClock::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "eol", aliases: &[], label: "EOL output signal", section: ADDITIONAL,
+        help: "End of life (low battery) indicator", detail: "",
+        reg: REG_STAT, shift: 3, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.eol), writable: false,
+        scale: None,
+        get: |i| i.eol as u32,
+        set: |i, v| { i.eol = Eol::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "xosc_ready", aliases: &[], label: "XOSC is ready", section: ADDITIONAL,
+        help: "Whether the crystal oscillator is running", detail: "",
+        reg: REG_STAT, shift: 2, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: false,
+        scale: None,
+        get: |i| i.xosc_ready as u32,
+        set: |i, v| { i.xosc_ready = v != 0; Ok(()) },
+    },
+    Field {
+        name: "pll_lock_rx", aliases: &[], label: "Rx PLL is locked", section: ADDITIONAL,
+        help: "Whether the Rx PLL is locked", detail: "",
+        reg: REG_STAT, shift: 1, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: false,
+        scale: None,
+        get: |i| i.pll_lock_rx as u32,
+        set: |i, v| { i.pll_lock_rx = v != 0; Ok(()) },
+    },
+    Field {
+        name: "pll_lock_tx", aliases: &[], label: "Tx PLL is locked", section: ADDITIONAL,
+        help: "Whether the Tx PLL is locked", detail: "",
+        reg: REG_STAT, shift: 0, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: false,
+        scale: None,
+        get: |i| i.pll_lock_tx as u32,
+        set: |i, v| { i.pll_lock_tx = v != 0; Ok(()) },
+    },
+    Field {
+        name: "iism_rx_disable", aliases: &[], label: "Disable IISM Rx (during Tx mode)", section: ADDITIONAL,
+        help: "Disables IISM Rx (during Tx mode)", detail: "",
+        reg: REG_IISM, shift: 7, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.iism_rx_disable as u32,
+        set: |i, v| { i.iism_rx_disable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "iism_tx_disable", aliases: &[], label: "Disable IISM Tx (during Rx mode)", section: ADDITIONAL,
+        help: "Disables IISM Tx (during Rx mode)", detail: "",
+        reg: REG_IISM, shift: 6, width: 1, min: 0, max: 1, units: "", kind: Kind::Bool, writable: true,
+        scale: None,
+        get: |i| i.iism_tx_disable as u32,
+        set: |i, v| { i.iism_tx_disable = v != 0; Ok(()) },
+    },
+    Field {
+        name: "iism_mode", aliases: &[], label: "IISM Mode", section: ADDITIONAL,
+        help: "Sets the IISM mode", detail: "",
+        reg: REG_IISM, shift: 4, width: 2, min: 0, max: 3, units: "", kind: Kind::Code(&OPTS.iism_mode), writable: true,
+        scale: None,
+        get: |i| i.iism_mode as u32,
+        set: |i, v| { i.iism_mode = IismMode::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "iism_clk_div", aliases: &[], label: "XTAL/CLK_OUT division factor", section: ADDITIONAL,
+        help: "Sets the XTAL/CLK_OUT division factor", detail: "",
+        reg: REG_IISM, shift: 0, width: 4, min: 0, max: 8, units: "", kind: Kind::Code(&OPTS.iism_clk_div), writable: true,
+        scale: None,
+        get: |i| i.iism_clk_div as u32,
+        set: |i, v| { i.iism_clk_div = v as u8; Ok(()) },
+    },
+    Field {
+        name: "r", aliases: &[], label: "Decimation/Interpolation factor", section: ADDITIONAL,
+        help: "Sets the interpolation/decimation factor", detail: "",
+        reg: REG_DIG_BRIDGE, shift: 3, width: 5, min: 8, max: 1728, units: "", kind: Kind::Factor, writable: true,
+        scale: None,
+        get: |i| i.r,
+        set: |i, v| { i.r = v; Ok(()) },
+    },
+    Field {
+        name: "iism_truncation", aliases: &[], label: "IISM truncation mode", section: ADDITIONAL,
+        help: "Sets the IISM truncation mode in Rx and Tx", detail: "",
+        reg: REG_DIG_BRIDGE, shift: 2, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.iism_truncation), writable: true,
+        scale: None,
+        get: |i| i.iism_truncation as u32,
+        set: |i, v| { i.iism_truncation = Truncation::try_from(v as u8)?; Ok(()) },
+    },
+    Field {
+        name: "iism_status_flag", aliases: &[], label: "IISM error status flag", section: ADDITIONAL,
+        help: "Set when the IISM settings don't make a valid frame", detail: "",
+        reg: REG_DIG_BRIDGE, shift: 1, width: 1, min: 0, max: 1, units: "", kind: Kind::Code(&OPTS.iism_status_flag), writable: false,
+        scale: None,
+        get: |i| i.iism_status_flag as u32,
+        set: |i, v| { i.iism_status_flag = v as u8; Ok(()) },
+    },
+];
+
+/// Looks a field up by its name or one of its aliases
+pub fn find(name: &str) -> Option<&'static Field> {
+    FIELDS.iter().find(|f| f.name == name || f.aliases.contains(&name))
+}
+
+impl Field {
+    /// Checks that `value` is in range for this field
+    pub fn check(&self, value: u32) -> Result<(), Sx1255Error> {
+        let valid = match self.kind {
+            Kind::Factor => VALID_R_VALUES.contains(&value),
+            _ => (self.min..=self.max).contains(&value),
+        };
+        if !valid {
+            return Err(Sx1255Error::InvalidValue(format!("{} must be {}", self.name, self.range())));
+        }
+        Ok(())
+    }
+
+    /// Describes the values the field accepts
+    pub fn range(&self) -> String {
+        match self.kind {
+            Kind::Bool => "true or false".to_string(),
+            Kind::Factor => "one of 8, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1536, \
+                9, 18, 27, 36, 54, 72, 108, 144, 216, 288, 432, 576, 864 or 1728".to_string(),
+            _ => format!("between {}-{}", self.min, self.max),
+        }
+    }
+
+    /// Units values can be given in instead of a code or plain number
+    pub fn units(&self) -> Option<&'static str> {
+        match (self.scale, self.kind) {
+            (Some(scale), _) => Some(scale.units),
+            (None, Kind::Number) if !self.units.is_empty() => Some(self.units),
+            _ => None,
+        }
+    }
+
+    /// Parses and checks a value as given on the command line
+    ///
+    /// Fields with [`units`](Field::units) also take a value in those units
+    /// with an optional SI prefix, e.g. `435.125M` or `-9.5dB`. Codes are
+    /// picked by the nearest value, plain integers are still codes.
+    pub fn parse(&self, s: &str) -> Result<u32, Sx1255Error> {
+        let value = match (self.kind, self.units()) {
+            (Kind::Bool, _) => s.parse::<bool>().map(|b| b as u32).map_err(|e| e.to_string()),
+            (_, Some(units)) if s.parse::<u32>().is_err() => self.parse_units(s, units),
+            _ => s.parse::<u32>().map_err(|e| e.to_string()),
+        };
+        let value = value.map_err(|e| Sx1255Error::InvalidValue(format!("{}: {}", self.name, e)))?;
+        self.check(value)?;
+        Ok(value)
+    }
+
+    // converts a value in engineering units to the nearest code, or to a
+    // whole number for fields without codes
+    fn parse_units(&self, s: &str, units: &str) -> Result<u32, String> {
+        let value = parse_si(s, units)
+            .ok_or_else(|| format!("{} isn't a number or a value in {}", s, units))?;
+        match self.scale {
+            Some(scale) => (self.min..=self.max)
+                .filter_map(|code| scale.values.get(code as usize)
+                    .filter(|v| !v.is_nan())
+                    .map(|v| (code, (v - value).abs())))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(code, _)| code)
+                .ok_or_else(|| format!("no code for {} {}", value, units)),
+            None if (0.0..=u32::MAX as f64).contains(&value) => Ok(value.round() as u32),
+            None => Err(format!("{} is out of range", s)),
+        }
+    }
+
+    /// Checks `value` and stores it in `sx1255_info`
+    pub fn apply(&self, sx1255_info: &mut SX1255Info, value: u32) -> Result<(), Sx1255Error> {
+        self.check(value)?;
+        (self.set)(sx1255_info, value)
+    }
+
+    /// Formats a value the way it is written in files
+    pub fn format(&self, value: u32) -> String {
+        match self.kind {
+            Kind::Bool => (value != 0).to_string(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Formats a value along with its meaning
+    pub fn describe(&self, value: u32) -> String {
+        match self.kind {
+            Kind::Code(opts) => match opts.get(value as usize) {
+                Some(opt) => format!("{} ({})", value, opt),
+                None => value.to_string(),
+            },
+            Kind::Hex => format!("0x{:02X}", value),
+            _ if !self.units.is_empty() => format!("{} {}", value, self.units),
+            _ => self.format(value),
+        }
+    }
+
+    /// Longer help listing what the values mean
+    pub fn long_help(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.detail.is_empty() {
+            lines.push(self.detail.to_string());
+        } else if let Kind::Code(opts) = self.kind {
+            for code in self.min..=self.max {
+                lines.push(format!("{}: {}", code, opts[code as usize]));
+            }
+        }
+        lines.push(format!("(must be {})", self.range()));
+        match (self.scale, self.units()) {
+            (Some(_), Some(units)) => lines.push(format!("(or a value in {}, which picks the nearest code)", units)),
+            (None, Some(units)) => lines.push(format!("(or a value in {} with an SI prefix like k or M)", units)),
+            _ => {},
+        }
+        lines.join("\n")
+    }
+}
+
+/// Parses a number with an optional SI prefix and unit, like `1.65kΩ` or `750k`
+pub fn parse_si(s: &str, units: &str) -> Option<f64> {
+    let s = s.trim();
+    // units like Hz or dB are often written in other cases, F isn't
+    let number = if units.chars().count() > 1 && s.to_lowercase().ends_with(&units.to_lowercase()) {
+        &s[..s.len() - units.len()]
+    } else {
+        s.strip_suffix(units).unwrap_or(s)
+    };
+    let number = number.trim_end();
+    let last = number.chars().last()?;
+    let multiplier = match last {
+        'f' => 1e-15,
+        'p' => 1e-12,
+        'n' => 1e-9,
+        'u' | 'µ' => 1e-6,
+        'm' => 1e-3,
+        'k' | 'K' => 1e3,
+        'M' => 1e6,
+        'G' => 1e9,
+        _ => 1.0,
+    };
+    let number = if multiplier == 1.0 { number } else { &number[..number.len() - last.len_utf8()] };
+    number.trim_end().parse::<f64>().ok().filter(|v| v.is_finite()).map(|v| v * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_match_registers() {
+        // every writable field must land in the bits the table says it uses
+        let base = SX1255Info::default();
+        let base_regs = base.to_registers(&Default::default()).unwrap().to_bytes();
+        for field in FIELDS.iter().filter(|f| f.writable && f.width <= 8) {
+            let mut changed = base;
+            let value = if (field.get)(&base) == field.min { field.max } else { field.min };
+            field.apply(&mut changed, value).unwrap();
+            let regs = changed.to_registers(&Default::default()).unwrap().to_bytes();
+            let mask = (((1_u16 << field.width) - 1) << field.shift) as u8;
+            for addr in 0..NUM_REGS {
+                let diff = regs[addr] ^ base_regs[addr];
+                if addr == field.reg as usize {
+                    assert!(diff != 0 && diff & !mask == 0, "{}", field.name);
+                } else {
+                    assert_eq!(diff, 0, "{}", field.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse_checks_range() {
+        assert_eq!(find("iomap0").unwrap().parse("3").unwrap(), 3);
+        assert!(find("io_map0").unwrap().parse("4").is_err());
+        assert!(find("rx_pga_bw").unwrap().parse("4").is_err());
+        assert!(find("r").unwrap().parse("10").is_err());
+        assert_eq!(find("rx_enable").unwrap().parse("true").unwrap(), 1);
+        assert!(find("tx_dac_bw").unwrap().parse("6").is_err());
+    }
+
+    #[test]
+    fn parse_takes_units() {
+        let parse = |name, s| find(name).unwrap().parse(s);
+        assert_eq!(parse("rx_freq", "435.125M").unwrap(), 435125000);
+        assert_eq!(parse("tx_freq", "438000 kHz").unwrap(), 438000000);
+        assert!(parse("rx_freq", "600M").is_err());
+        assert_eq!(parse("tx_mixer_gain", "-9.5dB").unwrap(), 14);
+        assert_eq!(parse("tx_mixer_gain", "-11 db").unwrap(), 13);
+        assert_eq!(parse("rx_pga_bw", "750k").unwrap(), 2);
+        assert_eq!(parse("rx_pga_bw", "700kHz").unwrap(), 2);
+        assert_eq!(parse("tx_pll_bw", "150k").unwrap(), 1);
+        assert_eq!(parse("tx_mixer_tank_res", "1.65k").unwrap(), 3);
+        assert_eq!(parse("tx_mixer_tank_res", "1.65kΩ").unwrap(), 3);
+        assert_eq!(parse("tx_mixer_tank_cap", "384fF").unwrap(), 3);
+        assert_eq!(parse("tx_filter_bw", "0.5M").unwrap(), 7);
+        assert_eq!(parse("tx_filter_bw", "530k").unwrap(), 9);
+        assert_eq!(parse("rx_lna_gain", "0dB").unwrap(), 1);
+        assert_eq!(parse("rx_lna_gain", "-100dB").unwrap(), 6);
+        // plain integers are still codes
+        assert_eq!(parse("tx_mixer_gain", "9").unwrap(), 9);
+        assert!(parse("tx_mixer_gain", "loud").is_err());
+        assert!(parse("iism_clk_div", "2k").is_err());
+    }
+}
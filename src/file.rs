@@ -0,0 +1,147 @@
+use std::fs::{File, read_to_string};
+use std::path::{Path, PathBuf};
+use std::io::{Write, Error};
+use chrono::prelude::*;
+
+use crate::fields::{Field, Kind, FIELDS};
+use crate::info::SX1255Info;
+
+/// Saves `sx1255_info` as a commented TOML file
+pub fn write_file(sx1255_info: SX1255Info, filename: &PathBuf) -> std::io::Result<()> {
+    // this doesn't use serialization because we want to put a bunch of
+    // comments in for people editing the file by hand
+    let mut file = File::create(filename)?;
+    write!(file, "# SX1255 configuration\n# created by sx1255-config on {}\n\n", Utc::now())?;
+    let mut commented = false;
+    for field in FIELDS.iter().filter(|f| f.writable) {
+        // commented fields are kept apart from their neighbours
+        let comments = comments(field);
+        if commented || !comments.is_empty() {
+            writeln!(file)?;
+        }
+        commented = !comments.is_empty();
+        for comment in comments {
+            writeln!(file, "# {}", comment)?;
+        }
+        let value = field.format((field.get)(&sx1255_info));
+        match field.kind {
+            Kind::Number if !field.units.is_empty() => writeln!(file, "{} = {} # {}", field.name, value, field.units)?,
+            Kind::Number => writeln!(file, "{} = {} # {}-{}", field.name, value, field.min, field.max)?,
+            Kind::Code(_) if !field.detail.is_empty() => writeln!(file, "{} = {} # {}-{}", field.name, value, field.min, field.max)?,
+            _ => writeln!(file, "{} = {}", field.name, value)?,
+        }
+    }
+
+    Ok(())
+}
+
+// comment lines explaining a field's values
+fn comments(field: &Field) -> Vec<String> {
+    match field.kind {
+        Kind::Code(_) if !field.detail.is_empty() => vec![field.detail.to_string()],
+        Kind::Code(opts) => (field.min..=field.max)
+            .map(|code| format!("{}: {}", code, opts[code as usize]))
+            .collect(),
+        Kind::Factor => vec![
+            "decimation/interpolation factor, valid values:".to_string(),
+            "set 1: 8, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1536".to_string(),
+            "set 2: 9, 18, 27, 36, 54, 72, 108, 144, 216, 288, 432, 576, 864, 1728".to_string(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Loads and validates a TOML file into `sx1255_info`
+///
+/// Settings missing from the file get their power on defaults.
+pub fn read_file(sx1255_info: &mut SX1255Info, filename: &Path) -> std::io::Result<()> {
+    let mut config = SX1255Info::default();
+    apply_file(&mut config, filename, &mut Vec::new())?;
+
+    // copy the values in the info struct
+    *sx1255_info = config;
+
+    Ok(())
+}
+
+/// Loads and validates a TOML file on top of `sx1255_info`
+///
+/// Only the settings in the file change, the rest keep their values.
+pub fn merge_file(sx1255_info: &mut SX1255Info, filename: &Path) -> std::io::Result<()> {
+    let mut config = *sx1255_info;
+    apply_file(&mut config, filename, &mut Vec::new())?;
+    *sx1255_info = config;
+    Ok(())
+}
+
+// applies the files in `include` and then the settings of the file itself,
+// `parents` holds the files including this one to catch loops
+fn apply_file(config: &mut SX1255Info, filename: &Path, parents: &mut Vec<PathBuf>) -> std::io::Result<()> {
+
+    let content = read_to_string(filename)?;
+    let mut table: toml::Table = match toml::from_str(&content) {
+        Ok(table) => table,
+        Err(e) => {
+            return Err(Error::other(e.message()));
+        },
+    };
+
+    // includes are relative to the file naming them
+    if let Some(include) = table.remove("include") {
+        let path = filename.canonicalize()?;
+        if parents.contains(&path) {
+            return Err(Error::other(format!("{} includes itself", filename.display())));
+        }
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        parents.push(path);
+        for name in include_names(include)? {
+            let included = dir.join(name);
+            apply_file(config, &included, parents)
+                .map_err(|e| Error::other(format!("{}: {}", included.display(), e)))?;
+        }
+        parents.pop();
+    }
+
+    for (key, value) in table {
+        let (field, value) = parse_setting(&key, &value).map_err(Error::other)?;
+        field.apply(config, value).map_err(|e| Error::other(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Checks one `key = value` setting from a file
+///
+/// Fields with units also take a string like `"435.125M"`, see
+/// [`Field::parse`].
+pub fn parse_setting(key: &str, value: &toml::Value) -> Result<(&'static Field, u32), String> {
+    let field = match FIELDS.iter().find(|f| f.name == key) {
+        Some(field) => field,
+        None => return Err(format!("unknown setting {}", key)),
+    };
+    if !field.writable {
+        return Err(format!("{} is read only", key));
+    }
+    let value = match (field.kind, value) {
+        (Kind::Bool, toml::Value::Boolean(b)) => *b as u32,
+        (Kind::Bool, _) => return Err(format!("{} must be true or false", key)),
+        (_, toml::Value::Integer(i)) if (0..=u32::MAX as i64).contains(i) => *i as u32,
+        (_, toml::Value::String(s)) if field.units().is_some() => field.parse(s).map_err(|e| e.to_string())?,
+        (_, _) => return Err(format!("{} must be {}", key, field.range())),
+    };
+    field.check(value).map_err(|e| e.to_string())?;
+    Ok((field, value))
+}
+
+fn include_names(include: toml::Value) -> std::io::Result<Vec<String>> {
+    let names = match include {
+        toml::Value::Array(names) => names,
+        _ => return Err(Error::other("include must be a list of file names")),
+    };
+    names.into_iter()
+        .map(|name| match name {
+            toml::Value::String(name) => Ok(name),
+            _ => Err(Error::other("include must be a list of file names")),
+        })
+        .collect()
+}
@@ -0,0 +1,173 @@
+use crate::XTAL_FREQ;
+use crate::error::Sx1255Error;
+
+/// XTAL/CLK_OUT division ratios, indexed by the IISM clock divider code
+pub static CLK_DIV_RATIOS: [u32; 9] = [1, 2, 4, 8, 12, 16, 24, 32, 48];
+
+// FRF registers hold the carrier frequency in steps of xtal / 2^20
+const FRF_SHIFT: u32 = 20;
+const FRF_MAX: u64 = 0xFFFFFF;
+
+/// The reference clock everything on the chip is derived from
+///
+/// The PLL frequency step, the I/Q sample rate and CLK_OUT all scale with
+/// the crystal (or TCXO) frequency, so conversions between register values
+/// and Hz go through here.
+///
+/// A per-board correction can be given so frequencies in Hz are the ones
+/// actually on air: `ppm` is how far the crystal is off (positive when it
+/// runs fast) and `offset` is a fixed error in Hz on top of that.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reference {
+    /// Nominal crystal frequency in Hz
+    pub xtal_freq: u32,
+    /// Crystal error in parts per million
+    pub ppm: f64,
+    /// Remaining carrier frequency error in Hz
+    pub offset: i32,
+}
+
+impl Default for Reference {
+    fn default() -> Reference {
+        Reference::new(XTAL_FREQ)
+    }
+}
+
+impl Reference {
+    /// Uses a crystal of `xtal_freq` Hz
+    pub fn new(xtal_freq: u32) -> Reference {
+        Reference { xtal_freq, ppm: 0.0, offset: 0 }
+    }
+
+    /// Applies a per-board frequency correction
+    pub fn with_correction(self, ppm: f64, offset: i32) -> Reference {
+        Reference { ppm, offset, ..self }
+    }
+
+    // frequency the crystal really runs at, in millihertz so the
+    // correction can be finer than 1 Hz
+    fn xtal_millihz(&self) -> u64 {
+        (self.xtal_freq as f64 * 1000.0 * (1.0 + self.ppm / 1e6)).round() as u64
+    }
+
+    /// Crystal frequency in Hz after correction
+    pub fn actual_xtal(&self) -> f64 {
+        self.xtal_millihz() as f64 / 1000.0
+    }
+
+    /// Size of one synthesizer step in Hz
+    pub fn step(&self) -> f64 {
+        self.actual_xtal() / (1 << FRF_SHIFT) as f64
+    }
+
+    /// Converts a 24 bit FRF register value to a carrier frequency, rounded to the nearest Hz
+    pub fn frf_to_freq(&self, frf: u32) -> u32 {
+        let scale = 1000 << FRF_SHIFT;
+        let freq = (frf as u64 * self.xtal_millihz() + scale / 2) / scale;
+        (freq as i64 + self.offset as i64).clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// Exact carrier frequency in Hz for a 24 bit FRF register value
+    pub fn frf_to_hz(&self, frf: u32) -> f64 {
+        frf as f64 * self.step() + self.offset as f64
+    }
+
+    /// Converts a carrier frequency in Hz to the nearest 24 bit FRF register value
+    pub fn freq_to_frf(&self, freq: u32) -> Result<u32, Sx1255Error> {
+        let xtal = self.xtal_millihz();
+        let target = (freq as i64 - self.offset as i64).max(0) as u64;
+        let frf = (((target * 1000) << FRF_SHIFT) + xtal / 2) / xtal;
+        if frf > FRF_MAX {
+            return Err(Sx1255Error::InvalidValue(format!(
+                "{} Hz is above the synthesizer range with a {} Hz reference", freq, self.xtal_freq)));
+        }
+        Ok(frf as u32)
+    }
+
+    /// Works out where the synthesizer lands when asked for `freq` Hz
+    pub fn tune(&self, freq: u32) -> Result<Tuning, Sx1255Error> {
+        let frf = self.freq_to_frf(freq)?;
+        Ok(Tuning { requested: freq, frf, actual: self.frf_to_hz(frf) })
+    }
+
+    /// I/Q sample rate in Hz for a decimation/interpolation factor
+    pub fn sample_rate(&self, r: u32) -> f64 {
+        self.actual_xtal() / r as f64
+    }
+
+    /// CLK_OUT frequency in Hz for an IISM clock divider code
+    pub fn clk_out(&self, clk_div: u8) -> Option<f64> {
+        CLK_DIV_RATIOS.get(clk_div as usize).map(|div| self.actual_xtal() / *div as f64)
+    }
+}
+
+/// A requested carrier frequency and the one the PLL actually produces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    /// Frequency asked for in Hz
+    pub requested: u32,
+    /// FRF register value used
+    pub frf: u32,
+    /// Frequency the synthesizer produces in Hz
+    pub actual: f64,
+}
+
+impl Tuning {
+    /// Difference between the actual and requested frequency in Hz
+    pub fn error(&self) -> f64 {
+        self.actual - self.requested as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_with_xtal() {
+        let xtal32 = Reference::new(32000000);
+        let xtal36 = Reference::new(36000000);
+        assert_eq!(xtal32.freq_to_frf(435000000).unwrap(), 0xD98000);
+        assert_eq!(xtal36.freq_to_frf(435000000).unwrap(), 0xC15555);
+        assert_eq!(xtal36.frf_to_freq(0xC00000), 432000000);
+        assert_eq!(xtal32.sample_rate(64), 500000.0);
+        assert_eq!(xtal36.sample_rate(72), 500000.0);
+        assert_eq!(xtal36.clk_out(4), Some(3000000.0));
+        assert_eq!(xtal36.clk_out(9), None);
+    }
+
+    #[test]
+    fn rounds_to_nearest_step() {
+        let xtal32 = Reference::default();
+        // 435006000 Hz is 14254276.608 steps
+        assert_eq!(xtal32.freq_to_frf(435006000).unwrap(), 14254277);
+        let tuning = xtal32.tune(435006000).unwrap();
+        assert_eq!(tuning.actual, 14254277.0 * 30.517578125);
+        assert!(tuning.error() > 0.0 && tuning.error() < xtal32.step() / 2.0);
+
+        // reading a frequency back and writing it again must not drift
+        for frf in [0xC0E38D, 0xD98000, 0xD98001, 0xDA0F5C] {
+            assert_eq!(xtal32.freq_to_frf(xtal32.frf_to_freq(frf)).unwrap(), frf);
+        }
+        assert!(xtal32.freq_to_frf(520000000).is_err());
+    }
+
+    #[test]
+    fn applies_correction() {
+        // a crystal 10 ppm fast needs a lower FRF for the same carrier
+        let fast = Reference::default().with_correction(10.0, 0);
+        assert_eq!(fast.freq_to_frf(435000000).unwrap(), 14253937);
+        assert_eq!(fast.frf_to_freq(14253937), 434999986);
+        assert_eq!(fast.actual_xtal(), 32000320.0);
+
+        let offset = Reference::default().with_correction(0.0, 500);
+        assert_eq!(offset.freq_to_frf(435000000).unwrap(), 14254064);
+        assert_eq!(offset.frf_to_freq(0xD98000), 435000500);
+
+        for reference in [fast, offset] {
+            for frf in [0xC0E38D, 0xD98000, 0xD98001] {
+                assert_eq!(reference.freq_to_frf(reference.frf_to_freq(frf)).unwrap(), frf);
+            }
+        }
+    }
+}
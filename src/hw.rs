@@ -0,0 +1,220 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use clap::Args;
+use serde::Deserialize;
+use spidev::{SpidevOptions, SpiModeFlags};
+
+use crate::band::TxBand;
+use crate::error::Sx1255Error;
+use crate::freq::Reference;
+use crate::{SPI_DEV, SPI_OPTS, GPIO_CHIP, RESET_LINE, TX_BANDS, XTAL_FREQ};
+
+/// System wide hardware profile
+pub static HW_CONFIG_FILE: &str = "/etc/sx1255/hw.toml";
+
+/// How the SX1255 is wired to the host
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HwConfig {
+    /// SPI device the chip is on
+    pub spi_dev: String,
+    /// SPI clock speed in Hz
+    pub spi_speed: u32,
+    /// SPI mode (0-3), the SX1255 uses mode 0
+    pub spi_mode: u8,
+    /// GPIO chip the reset line is on
+    pub gpio_chip: String,
+    /// GPIO line connected to the reset pin
+    pub reset_line: u32,
+    /// Whether reset is asserted by driving the line low
+    pub reset_active_low: bool,
+    /// Reference crystal (or TCXO) frequency in Hz
+    pub xtal_freq: u32,
+    /// How far this board's crystal is off in ppm, positive when it runs fast
+    pub xtal_ppm: f64,
+    /// Fixed carrier frequency error in Hz, positive when it is high
+    pub freq_offset: i32,
+    /// Frequencies the PA driver may be enabled on, as `[low, high]` pairs in Hz
+    pub tx_bands: Vec<TxBand>,
+}
+
+// the M17 SX1255 HAT on a Raspberry Pi
+impl Default for HwConfig {
+    fn default() -> HwConfig {
+        HwConfig {
+            spi_dev: SPI_DEV.to_string(),
+            spi_speed: SPI_OPTS.max_speed_hz.unwrap_or(500000),
+            spi_mode: 0,
+            gpio_chip: GPIO_CHIP.to_string(),
+            reset_line: RESET_LINE,
+            reset_active_low: false,
+            xtal_freq: XTAL_FREQ,
+            xtal_ppm: 0.0,
+            freq_offset: 0,
+            tx_bands: TX_BANDS.to_vec(),
+        }
+    }
+}
+
+impl HwConfig {
+    /// Reads a hardware profile, keys that are missing keep their defaults
+    pub fn load(path: &Path) -> Result<HwConfig, Sx1255Error> {
+        let content = read_to_string(path)
+            .map_err(|e| Sx1255Error::Config(format!("{}: {}", path.display(), e)))?;
+        let hw: HwConfig = toml::from_str(&content)
+            .map_err(|e| Sx1255Error::Config(format!("{}: {}", path.display(), e.message())))?;
+        hw.validate()?;
+        Ok(hw)
+    }
+
+    /// Like [`HwConfig::load`], but a missing file gives the defaults
+    pub fn load_or_default(path: &Path) -> Result<HwConfig, Sx1255Error> {
+        if path.exists() {
+            HwConfig::load(path)
+        } else {
+            Ok(HwConfig::default())
+        }
+    }
+
+    /// Checks values that deserialization can't
+    pub fn validate(&self) -> Result<(), Sx1255Error> {
+        if self.spi_mode > 3 {
+            return Err(Sx1255Error::Config("spi_mode must be between 0-3".to_string()));
+        }
+        if self.spi_speed == 0 {
+            return Err(Sx1255Error::Config("spi_speed must be greater than 0".to_string()));
+        }
+        // the datasheet allows anything between 32 and 36.9 MHz
+        if !(32000000..=36900000).contains(&self.xtal_freq) {
+            return Err(Sx1255Error::Config("xtal_freq must be between 32000000-36900000".to_string()));
+        }
+        if !(-200.0..=200.0).contains(&self.xtal_ppm) {
+            return Err(Sx1255Error::Config("xtal_ppm must be between -200-200".to_string()));
+        }
+        if let Some(band) = self.tx_bands.iter().find(|band| band.low > band.high) {
+            return Err(Sx1255Error::Config(format!("tx_bands {} starts above where it ends", band)));
+        }
+        Ok(())
+    }
+
+    /// Reference clock for this profile
+    pub fn reference(&self) -> Reference {
+        Reference::new(self.xtal_freq).with_correction(self.xtal_ppm, self.freq_offset)
+    }
+
+    /// SPI settings for this profile
+    pub fn spi_options(&self) -> SpidevOptions {
+        let spi_mode = match self.spi_mode {
+            1 => SpiModeFlags::SPI_MODE_1,
+            2 => SpiModeFlags::SPI_MODE_2,
+            3 => SpiModeFlags::SPI_MODE_3,
+            _ => SpiModeFlags::SPI_MODE_0,
+        };
+        SpidevOptions {
+            max_speed_hz: Some(self.spi_speed),
+            spi_mode: Some(spi_mode),
+            ..SPI_OPTS
+        }
+    }
+}
+
+/// Hardware settings, these override the hardware profile
+///
+/// Flattened into the command line of each tool, so they can also come from
+/// the `SX1255_*` environment variables.
+#[derive(Debug, Clone, Default, Args)]
+pub struct HwArgs {
+    /// hardware profile [default: /etc/sx1255/hw.toml]
+    #[arg(long, global = true, env = "SX1255_HW_CONFIG")]
+    pub hw_config: Option<PathBuf>,
+
+    /// SPI device
+    #[arg(long, global = true, env = "SX1255_SPI_DEV")]
+    pub spi_dev: Option<String>,
+
+    /// SPI clock speed in Hz
+    #[arg(long, global = true, env = "SX1255_SPI_SPEED")]
+    pub spi_speed: Option<u32>,
+
+    /// SPI mode
+    #[arg(long, global = true, env = "SX1255_SPI_MODE", value_parser=clap::value_parser!(u8).range(0..4))]
+    pub spi_mode: Option<u8>,
+
+    /// GPIO chip the reset line is on
+    #[arg(long, global = true, env = "SX1255_GPIO_CHIP")]
+    pub gpio_chip: Option<String>,
+
+    /// GPIO line connected to the reset pin
+    #[arg(long, global = true, env = "SX1255_RESET_LINE")]
+    pub reset_line: Option<u32>,
+
+    /// reset is asserted by driving the line low
+    #[arg(long, global = true, env = "SX1255_RESET_ACTIVE_LOW")]
+    pub reset_active_low: Option<bool>,
+
+    /// reference crystal frequency in Hz
+    #[arg(long, global = true, env = "SX1255_XTAL_FREQ")]
+    pub xtal_freq: Option<u32>,
+
+    /// reference crystal error in ppm, positive when it runs fast
+    #[arg(long, global = true, env = "SX1255_XTAL_PPM", allow_negative_numbers = true)]
+    pub xtal_ppm: Option<f64>,
+
+    /// carrier frequency error in Hz, positive when it is high
+    #[arg(long, global = true, env = "SX1255_FREQ_OFFSET", allow_negative_numbers = true)]
+    pub freq_offset: Option<i32>,
+}
+
+impl HwArgs {
+    /// Loads the hardware profile and applies the overrides
+    pub fn resolve(&self) -> Result<HwConfig, Sx1255Error> {
+        let mut hw = match &self.hw_config {
+            Some(path) => HwConfig::load(path)?,
+            None => HwConfig::load_or_default(HW_CONFIG_FILE.as_ref())?,
+        };
+        if let Some(spi_dev) = &self.spi_dev { hw.spi_dev = spi_dev.clone() };
+        if let Some(spi_speed) = self.spi_speed { hw.spi_speed = spi_speed };
+        if let Some(spi_mode) = self.spi_mode { hw.spi_mode = spi_mode };
+        if let Some(gpio_chip) = &self.gpio_chip { hw.gpio_chip = gpio_chip.clone() };
+        if let Some(reset_line) = self.reset_line { hw.reset_line = reset_line };
+        if let Some(reset_active_low) = self.reset_active_low { hw.reset_active_low = reset_active_low };
+        if let Some(xtal_freq) = self.xtal_freq { hw.xtal_freq = xtal_freq };
+        if let Some(xtal_ppm) = self.xtal_ppm { hw.xtal_ppm = xtal_ppm };
+        if let Some(freq_offset) = self.freq_offset { hw.freq_offset = freq_offset };
+        hw.validate()?;
+        Ok(hw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+
+    #[test]
+    fn load_keeps_defaults() {
+        let path = std::env::temp_dir().join(format!("sx1255-hw-{}.toml", std::process::id()));
+        write(&path, "spi_dev = \"/dev/spidev1.0\"\nreset_active_low = true\n").unwrap();
+        let hw = HwConfig::load(&path).unwrap();
+        assert_eq!(hw.spi_dev, "/dev/spidev1.0");
+        assert!(hw.reset_active_low);
+        assert_eq!(hw.reset_line, RESET_LINE);
+
+        write(&path, "spi_mode = 4\n").unwrap();
+        assert!(HwConfig::load(&path).is_err());
+        write(&path, "xtal_freq = 26000000\n").unwrap();
+        assert!(HwConfig::load(&path).is_err());
+        write(&path, "xtal_ppm = 1000.0\n").unwrap();
+        assert!(HwConfig::load(&path).is_err());
+        write(&path, "tx_bands = [[430000000, 440000000], [144000000, 146000000]]\n").unwrap();
+        assert_eq!(HwConfig::load(&path).unwrap().tx_bands[1], TxBand { low: 144000000, high: 146000000 });
+        write(&path, "tx_bands = [[440000000, 430000000]]\n").unwrap();
+        assert!(HwConfig::load(&path).is_err());
+        write(&path, "reset_pin = 4\n").unwrap();
+        assert!(HwConfig::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(HwConfig::load_or_default(&path).unwrap(), HwConfig::default());
+        assert!(HwConfig::load(&path).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+use std::io;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::bus::RegisterBus;
+use crate::error::{Mismatch, Sx1255Error};
+use crate::freq::Reference;
+use crate::fields::FIELDS;
+use crate::regs::*;
+use crate::status::wait_tx_lock;
+
+// how long a retuned Tx gets to lock before the PA driver goes back on
+const RELOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Reads a single register
+pub fn sx1255_readreg<B: RegisterBus>(bus: &mut B, addr: u8) -> io::Result<u8> {
+    let tx_buf = [addr, 0];
+    let mut rx_buf = [0_u8; 2];
+    bus.transfer(&tx_buf, &mut rx_buf)?;
+    Ok(rx_buf[1])
+}
+
+/// Writes a single register, returning its value before the write
+pub fn sx1255_writereg<B: RegisterBus>(bus: &mut B, addr: u8, val: u8) -> io::Result<u8> {
+    let tx_buf = [addr | 0b10000000, val];
+    let mut rx_buf = [0_u8; 2];
+    bus.transfer(&tx_buf, &mut rx_buf)?;
+    Ok(rx_buf[1])
+}
+
+/// Reads consecutive registers starting at `addr` in a single burst
+pub fn sx1255_readburst<B: RegisterBus>(bus: &mut B, addr: u8, vals: &mut [u8]) -> io::Result<()> {
+    let mut tx_buf = vec![0_u8; vals.len() + 1];
+    tx_buf[0] = addr;
+    let mut rx_buf = vec![0_u8; tx_buf.len()];
+    bus.transfer(&tx_buf, &mut rx_buf)?;
+    vals.copy_from_slice(&rx_buf[1..]);
+    Ok(())
+}
+
+/// Writes consecutive registers starting at `addr` in a single burst
+pub fn sx1255_writeburst<B: RegisterBus>(bus: &mut B, addr: u8, vals: &[u8]) -> io::Result<()> {
+    let mut tx_buf = vec![addr | 0b10000000];
+    tx_buf.extend_from_slice(vals);
+    let mut rx_buf = vec![0_u8; tx_buf.len()];
+    bus.transfer(&tx_buf, &mut rx_buf)
+}
+
+/// Decoded contents of the SX1255 register file
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[serde(default)]
+pub struct SX1255Info {
+    pub driver_enable: bool,
+    pub tx_enable: bool,
+    pub rx_enable: bool,
+    pub ref_enable: bool,
+    pub rx_freq: u32,
+    pub tx_freq: u32,
+    pub version: u8,
+    pub tx_dac_gain: DacGain,
+    pub tx_mixer_gain: u8,
+    pub tx_mixer_tank_cap: u8,
+    pub tx_mixer_tank_res: TankRes,
+    pub tx_pll_bw: PllBw,
+    pub tx_filter_bw: u8,
+    pub tx_dac_bw: u8,
+    pub rx_lna_gain: LnaGain,
+    pub rx_pga_gain: u8,
+    pub rx_zin_200: RxZin,
+    pub rx_adc_bw: u8,
+    pub rx_adc_trim: u8,
+    pub rx_pga_bw: PgaBw,
+    pub rx_pll_bw: PllBw,
+    pub rx_adc_temp: bool,
+    pub iomap0: u8,
+    pub iomap1: u8,
+    pub iomap2: u8,
+    pub iomap3: u8,
+    pub dig_loopback_en: bool,
+    pub rf_loopback_en: bool,
+    pub ckout_enable: ClkOut,
+    pub ck_select_tx_dac: DacClock,
+    pub eol: Eol,
+    pub xosc_ready: bool,
+    pub pll_lock_rx: bool,
+    pub pll_lock_tx: bool,
+    pub iism_rx_disable: bool,
+    pub iism_tx_disable: bool,
+    pub iism_mode: IismMode,
+    pub iism_clk_div: u8,
+    pub r: u32,
+    pub iism_truncation: Truncation,
+    pub iism_status_flag: u8,
+}
+
+// power on defaults for the device
+impl Default for SX1255Info {
+    fn default() -> SX1255Info {
+        SX1255Info {
+            driver_enable: false,
+            tx_enable: false,
+            rx_enable: false,
+            ref_enable: true,
+            rx_freq: 385777770,
+            tx_freq: 385777770,
+            version: 0x11,
+            tx_dac_gain: DacGain::Minus3,
+            tx_mixer_gain: 12,
+            tx_mixer_tank_cap: 3,
+            tx_mixer_tank_res: TankRes::Open,
+            tx_pll_bw: PllBw::Bw300k,
+            tx_filter_bw: 0,
+            tx_dac_bw: 2,
+            rx_lna_gain: LnaGain::G1,
+            rx_pga_gain: 15,
+            rx_zin_200: RxZin::Ohm200,
+            rx_adc_bw: 7,
+            rx_adc_trim: 7,
+            rx_pga_bw: PgaBw::Bw1000k,
+            rx_pll_bw: PllBw::Bw300k,
+            rx_adc_temp: false,
+            iomap0: 0, 
+            iomap1: 0,
+            iomap2: 0,
+            iomap3: 0,
+            dig_loopback_en: false,
+            rf_loopback_en: false,
+            ckout_enable: ClkOut::Enabled,
+            ck_select_tx_dac: DacClock::Internal,
+            eol: Eol::Normal,
+            xosc_ready: true,
+            pll_lock_rx: false,
+            pll_lock_tx: false,
+            iism_rx_disable: false,
+            iism_tx_disable: false,
+            iism_mode: IismMode::A,
+            iism_clk_div: 0,
+            r: 8,
+            iism_truncation: Truncation::Msb,
+            iism_status_flag: 0,
+        }
+
+    }
+}
+
+/// Prints a human readable report of the device state
+pub fn print_info(sx1255_info: SX1255Info, reference: &Reference) {
+    let clk_out = match reference.clk_out(sx1255_info.iism_clk_div) {
+        Some(clk_out) => format!("{} Hz", clk_out),
+        None => "not used".to_string(),
+    };
+    println!("
+Reference
+
+     Reference crystal frequency: {xtal_freq} Hz
+            Frequency correction: {ppm:+} ppm, {offset:+} Hz
+              PLL frequency step: {step} Hz
+                 I/Q sample rate: {sample_rate} Hz
+               CLK_OUT frequency: {clk_out}",
+        xtal_freq   = reference.xtal_freq,
+        ppm         = reference.ppm,
+        offset      = reference.offset,
+        step        = reference.step(),
+        sample_rate = reference.sample_rate(sx1255_info.r),
+        clk_out     = clk_out,
+    );
+
+    let mut section = "";
+    for field in FIELDS.iter() {
+        if field.section != section {
+            section = field.section;
+            println!("\n{}\n", section);
+        }
+        println!("{:>32}: {}", field.label, field.describe((field.get)(&sx1255_info)));
+    }
+    println!();
+}
+
+/// Prints every register in hex and binary along with the fields it holds
+pub fn print_dump(regs: &[u8; NUM_REGS], reference: &Reference) {
+    let sx1255_info = SX1255Info::from_registers(&Registers::from_bytes(regs), reference);
+    for (addr, reg) in regs.iter().enumerate() {
+        println!("0x{:02X} {:<12} 0x{:02X} {:08b}", addr, REG_NAMES[addr], reg, reg);
+        for field in FIELDS.iter().filter(|f| f.reg as usize == addr) {
+            let bits = match field.width {
+                1 => format!("[{}]", field.shift),
+                width => format!("[{}:{}]", field.shift + width - 1, field.shift),
+            };
+            println!("     {:<7} {} = {}", bits, field.name, field.describe((field.get)(&sx1255_info)));
+        }
+    }
+}
+
+/// Calculates the decimation/interpolation factor from its register fields
+pub fn calc_r(mant: u8, m: u8, n: u8) -> u32 {
+    // r = MANT*3^m*2^n
+    // where MANT is 8 for the 1st set and 9 for the second set,
+    // m can be 0 or 1, and n is an integer between 0 and 6
+    
+    (mant as u32) * 3_u32.pow(m.into()) * 2_u32.pow(n.into())
+}
+
+/// Decimation/interpolation factors supported by the digital bridge
+pub static VALID_R_VALUES: [u32; 28]  = [
+    8, 16, 24, 32, 48, 64, 96, 128, 192, 256, 384, 512, 768, 1536,  // set 1
+    9, 18, 27, 36, 54, 72, 108, 144, 216, 288, 432, 576, 864, 1728, // set 2
+];
+
+/// Splits a decimation/interpolation factor into MANT, m and n
+pub fn r_to_mant_m_n(r: u32) -> Result<(u8, u8, u8), Sx1255Error> {
+// the easiest way I could think to do this was with a simple match
+    Ok(match r {
+        // set 1
+        8    => (8,0,0),
+        16   => (8,0,1),
+        24   => (8,1,0),
+        32   => (8,0,2),
+        48   => (8,1,1),
+        64   => (8,0,3),
+        96   => (8,1,2),
+        128  => (8,0,4),
+        192  => (8,1,3),
+        256  => (8,0,5),
+        384  => (8,1,4),
+        512  => (8,0,6),
+        768  => (8,1,5),
+        1536 => (8,1,6),
+        // set 2
+        9    => (9,0,0),
+        18   => (9,0,1),
+        27   => (9,1,0),
+        36   => (9,0,2),
+        54   => (9,1,1),
+        72   => (9,0,3),
+        108  => (9,1,2),
+        144  => (9,0,4),
+        216  => (9,1,3),
+        288  => (9,0,5),
+        432  => (9,1,4),
+        576  => (9,0,6),
+        864  => (9,1,5),
+        1728 => (9,1,6),
+        _    => return Err(Sx1255Error::InvalidValue(format!("{} is not a valid r value", r))),
+    })
+}
+
+impl SX1255Info {
+    /// Decodes the register file into device state
+    pub fn from_registers(regs: &Registers, reference: &Reference) -> SX1255Info {
+        SX1255Info {
+            driver_enable:     regs.mode.driver_enable,
+            tx_enable:         regs.mode.tx_enable,
+            rx_enable:         regs.mode.rx_enable,
+            ref_enable:        regs.mode.ref_enable,
+            rx_freq:           reference.frf_to_freq(regs.frf_rx),
+            tx_freq:           reference.frf_to_freq(regs.frf_tx),
+            version:           regs.version,
+            tx_dac_gain:       regs.txfe1.dac_gain,
+            tx_mixer_gain:     regs.txfe1.mixer_gain,
+            tx_mixer_tank_cap: regs.txfe2.mixer_tank_cap,
+            tx_mixer_tank_res: regs.txfe2.mixer_tank_res,
+            tx_pll_bw:         regs.txfe3.pll_bw,
+            tx_filter_bw:      regs.txfe3.filter_bw,
+            tx_dac_bw:         regs.txfe4.dac_bw,
+            rx_lna_gain:       regs.rxfe1.lna_gain,
+            rx_pga_gain:       regs.rxfe1.pga_gain,
+            rx_zin_200:        regs.rxfe1.zin,
+            rx_adc_bw:         regs.rxfe2.adc_bw,
+            rx_adc_trim:       regs.rxfe2.adc_trim,
+            rx_pga_bw:         regs.rxfe2.pga_bw,
+            rx_pll_bw:         regs.rxfe3.pll_bw,
+            rx_adc_temp:       regs.rxfe3.adc_temp,
+            iomap0:            regs.iomap.map0,
+            iomap1:            regs.iomap.map1,
+            iomap2:            regs.iomap.map2,
+            iomap3:            regs.iomap.map3,
+            dig_loopback_en:   regs.ck_sel.dig_loopback_en,
+            rf_loopback_en:    regs.ck_sel.rf_loopback_en,
+            ckout_enable:      regs.ck_sel.ckout_enable,
+            ck_select_tx_dac:  regs.ck_sel.ck_select_tx_dac,
+            eol:               regs.stat.eol,
+            xosc_ready:        regs.stat.xosc_ready,
+            pll_lock_rx:       regs.stat.pll_lock_rx,
+            pll_lock_tx:       regs.stat.pll_lock_tx,
+            iism_rx_disable:   regs.iism.rx_disable,
+            iism_tx_disable:   regs.iism.tx_disable,
+            iism_mode:         regs.iism.mode,
+            iism_clk_div:      regs.iism.clk_div,
+            r:                 calc_r(regs.dig_bridge.mant, regs.dig_bridge.m, regs.dig_bridge.n),
+            iism_truncation:   regs.dig_bridge.truncation,
+            iism_status_flag:  regs.dig_bridge.status_flag as u8,
+        }
+    }
+
+    /// Encodes the device state into the register file
+    pub fn to_registers(&self, reference: &Reference) -> Result<Registers, Sx1255Error> {
+        let (mant, m, n) = r_to_mant_m_n(self.r)?;
+        Ok(Registers {
+            mode: Mode {
+                driver_enable: self.driver_enable,
+                tx_enable:     self.tx_enable,
+                rx_enable:     self.rx_enable,
+                ref_enable:    self.ref_enable,
+            },
+            frf_rx: reference.freq_to_frf(self.rx_freq)?,
+            frf_tx: reference.freq_to_frf(self.tx_freq)?,
+            version: self.version,
+            txfe1: TxFe1 { dac_gain: self.tx_dac_gain, mixer_gain: self.tx_mixer_gain },
+            txfe2: TxFe2 { mixer_tank_cap: self.tx_mixer_tank_cap, mixer_tank_res: self.tx_mixer_tank_res },
+            txfe3: TxFe3 { pll_bw: self.tx_pll_bw, filter_bw: self.tx_filter_bw },
+            txfe4: TxFe4 { dac_bw: self.tx_dac_bw },
+            rxfe1: RxFe1 { lna_gain: self.rx_lna_gain, pga_gain: self.rx_pga_gain, zin: self.rx_zin_200 },
+            rxfe2: RxFe2 { adc_bw: self.rx_adc_bw, adc_trim: self.rx_adc_trim, pga_bw: self.rx_pga_bw },
+            rxfe3: RxFe3 { pll_bw: self.rx_pll_bw, adc_temp: self.rx_adc_temp },
+            iomap: IoMap { map0: self.iomap0, map1: self.iomap1, map2: self.iomap2, map3: self.iomap3 },
+            ck_sel: CkSel {
+                dig_loopback_en:  self.dig_loopback_en,
+                rf_loopback_en:   self.rf_loopback_en,
+                ckout_enable:     self.ckout_enable,
+                ck_select_tx_dac: self.ck_select_tx_dac,
+            },
+            stat: Stat {
+                eol:         self.eol,
+                xosc_ready:  self.xosc_ready,
+                pll_lock_rx: self.pll_lock_rx,
+                pll_lock_tx: self.pll_lock_tx,
+            },
+            iism: Iism {
+                rx_disable: self.iism_rx_disable,
+                tx_disable: self.iism_tx_disable,
+                mode:       self.iism_mode,
+                clk_div:    self.iism_clk_div,
+            },
+            dig_bridge: DigBridge {
+                mant,
+                m,
+                n,
+                truncation:  self.iism_truncation,
+                status_flag: self.iism_status_flag != 0,
+            },
+        })
+    }
+}
+
+/// Reads all registers into `sx1255_info`
+pub fn get_info<B: RegisterBus>(bus: &mut B, sx1255_info: &mut SX1255Info, reference: &Reference) -> Result<(), Sx1255Error> {
+    let regs = read_registers(bus)?;
+    *sx1255_info = SX1255Info::from_registers(&Registers::from_bytes(&regs), reference);
+    Ok(())
+}
+
+/// Lists the fields whose values differ between `expected` and `actual`
+pub fn compare_info(expected: &SX1255Info, actual: &SX1255Info) -> Vec<Mismatch> {
+    FIELDS.iter()
+        .filter(|field| field.writable)
+        .filter(|field| (field.get)(expected) != (field.get)(actual))
+        .map(|field| Mismatch {
+            field: field.name.to_string(),
+            expected: field.describe((field.get)(expected)),
+            actual: field.describe((field.get)(actual)),
+        })
+        .collect()
+}
+
+/// Reads the registers back and checks they hold what `regs` wrote
+pub fn verify_info<B: RegisterBus>(bus: &mut B, regs: &[u8; NUM_REGS], reference: &Reference) -> Result<(), Sx1255Error> {
+    let read_back = read_registers(bus)?;
+    let differs = (0..NUM_REGS).any(|i| (regs[i] ^ read_back[i]) & WRITABLE_MASKS[i] != 0);
+    if !differs {
+        return Ok(());
+    }
+
+    let expected = SX1255Info::from_registers(&Registers::from_bytes(regs), reference);
+    let actual = SX1255Info::from_registers(&Registers::from_bytes(&read_back), reference);
+    Err(Sx1255Error::Verify(compare_info(&expected, &actual)))
+}
+
+fn read_registers<B: RegisterBus>(bus: &mut B) -> Result<[u8; NUM_REGS], Sx1255Error> {
+    let mut regs = [0_u8; NUM_REGS];
+    for (addr, reg) in regs.iter_mut().enumerate() {
+        *reg = sx1255_readreg(bus, addr as u8)?;
+    }
+    Ok(regs)
+}
+
+/// Works out the register writes needed to go from `current` to `desired`
+///
+/// Only registers that change are written, each entry being a burst starting
+/// at the given address. If the Tx frequency or front-end changes while the
+/// transmitter is on, the PA driver and Tx are switched off first so nothing
+/// is radiated with stale settings. Whenever the PA driver is to come on, it
+/// is left off in the final MODE write since the Tx PLL has to lock first.
+/// Frequencies are written as a whole and MODE always comes last.
+pub(crate) fn plan_writes(current: &[u8; NUM_REGS], desired: &[u8; NUM_REGS]) -> Vec<(u8, Vec<u8>)> {
+    let changed = |addr: u8| (current[addr as usize] ^ desired[addr as usize]) & WRITABLE_MASKS[addr as usize] != 0;
+    let mut writes = Vec::new();
+
+    let mut mode = current[REG_MODE as usize];
+    let mut last_mode = desired[REG_MODE as usize];
+    let tx_regs = [REG_FRFH_TX, REG_FRFM_TX, REG_FRFL_TX, REG_TXFE1, REG_TXFE2, REG_TXFE3, REG_TXFE4];
+    let keyed = Mode::decode(mode).driver_enable || Mode::decode(mode).tx_enable;
+    if keyed && tx_regs.into_iter().any(changed) {
+        let mut unkeyed = Mode::decode(mode);
+        unkeyed.driver_enable = false;
+        unkeyed.tx_enable = false;
+        mode = unkeyed.encode();
+        writes.push((REG_MODE, vec![mode]));
+    }
+    // the PA driver comes on, from idle or after the unkey above
+    if !Mode::decode(mode).driver_enable {
+        let mut relocking = Mode::decode(last_mode);
+        relocking.driver_enable = false;
+        last_mode = relocking.encode();
+    }
+
+    let mut addr = REG_MODE + 1;
+    while (addr as usize) < NUM_REGS {
+        if addr == REG_FRFH_RX || addr == REG_FRFH_TX {
+            if (addr..addr + 3).any(changed) {
+                writes.push((addr, desired[addr as usize..addr as usize + 3].to_vec()));
+            }
+            addr += 3;
+            continue;
+        }
+        if changed(addr) {
+            writes.push((addr, vec![desired[addr as usize]]));
+        }
+        addr += 1;
+    }
+
+    if (mode ^ last_mode) & Mode::MASK != 0 {
+        writes.push((REG_MODE, vec![last_mode]));
+    }
+    writes
+}
+
+/// Writes the registers that differ from `sx1255_info` and verifies them
+///
+/// The PA driver is only turned on, whether keying from idle or after a keyed
+/// Tx is retuned, once the Tx PLL has locked. If it doesn't lock within
+/// 100 ms the PA driver is left off and [`Sx1255Error::Lock`] returned.
+pub(crate) fn set_info<B: RegisterBus>(bus: &mut B, sx1255_info: SX1255Info, reference: &Reference) -> Result<(), Sx1255Error> {
+    // encode everything first so an invalid value doesn't leave the chip
+    // half configured
+    let regs = sx1255_info.to_registers(reference)?.to_bytes();
+    let current = read_registers(bus)?;
+    let writes = plan_writes(&current, &regs);
+    let written_mode = writes.iter().rev()
+        .find(|(addr, _)| *addr == REG_MODE)
+        .map_or(current[REG_MODE as usize], |(_, vals)| vals[0]);
+    for (addr, vals) in writes {
+        sx1255_writeburst(bus, addr, &vals)?;
+    }
+    if sx1255_info.driver_enable && !Mode::decode(written_mode).driver_enable {
+        wait_tx_lock(bus, RELOCK_TIMEOUT)?;
+        sx1255_writereg(bus, REG_MODE, regs[REG_MODE as usize])?;
+    }
+    verify_info(bus, &regs, reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::MockBus;
+
+    #[test]
+    fn verify_reports_fields() {
+        let mut bus = MockBus::new();
+        let mut sx1255_info = SX1255Info { rx_freq: 435000000, tx_freq: 435000000, ..Default::default() };
+        set_info(&mut bus, sx1255_info, &Reference::default()).unwrap();
+
+        bus.ignore_writes = true;
+        sx1255_info.rx_lna_gain = LnaGain::G3;
+        match set_info(&mut bus, sx1255_info, &Reference::default()) {
+            Err(Sx1255Error::Verify(mismatches)) => {
+                assert_eq!(mismatches, vec![Mismatch {
+                    field: "rx_lna_gain".to_string(),
+                    expected: "3 (G3 = highest gain power - 12 dB)".to_string(),
+                    actual: "1 (G1 = highest gain power - 0 dB)".to_string(),
+                }]);
+            },
+            other => panic!("expected verification failure, got {:?}", other),
+        }
+    }
+
+    fn keyed_regs() -> [u8; NUM_REGS] {
+        let sx1255_info = SX1255Info {
+            driver_enable: true,
+            tx_enable: true,
+            rx_enable: true,
+            ..Default::default()
+        };
+        sx1255_info.to_registers(&Reference::default()).unwrap().to_bytes()
+    }
+
+    #[test]
+    fn plan_writes_nothing_when_unchanged() {
+        let regs = keyed_regs();
+        assert!(plan_writes(&regs, &regs).is_empty());
+    }
+
+    #[test]
+    fn plan_writes_unkeys_before_tx_retune() {
+        let current = keyed_regs();
+        let mut desired = current;
+        desired[REG_FRFL_TX as usize] ^= 1;
+        desired[REG_RXFE1 as usize] ^= 1 << 5;
+        let unkeyed = Mode { driver_enable: false, tx_enable: false, rx_enable: true, ref_enable: true };
+        let relocking = Mode { tx_enable: true, ..unkeyed };
+        assert_eq!(plan_writes(&current, &desired), vec![
+            (REG_MODE, vec![unkeyed.encode()]),
+            (REG_FRFH_TX, desired[REG_FRFH_TX as usize..REG_FRFH_TX as usize + 3].to_vec()),
+            (REG_RXFE1, vec![desired[REG_RXFE1 as usize]]),
+            (REG_MODE, vec![relocking.encode()]),
+        ]);
+    }
+
+    #[test]
+    fn set_info_rekeys_after_lock() {
+        let mut bus = MockBus::new();
+        let mut sx1255_info = SX1255Info {
+            driver_enable: true,
+            tx_enable:     true,
+            tx_freq:       435000000,
+            ..Default::default()
+        };
+        // keying from idle waits for the Tx PLL too
+        match set_info(&mut bus, sx1255_info, &Reference::default()) {
+            Err(Sx1255Error::Lock(_)) => {},
+            other => panic!("expected lock failure, got {:?}", other),
+        }
+        let mode = Mode::decode(bus.regs[REG_MODE as usize]);
+        assert!(mode.tx_enable && !mode.driver_enable);
+        bus.regs[REG_STAT as usize] |= 0b0001;
+        set_info(&mut bus, sx1255_info, &Reference::default()).unwrap();
+        assert!(Mode::decode(bus.regs[REG_MODE as usize]).driver_enable);
+
+        // the Tx PLL never locks again, so the driver stays off
+        bus.regs[REG_STAT as usize] &= !0b0001;
+        sx1255_info.tx_freq = 438000000;
+        match set_info(&mut bus, sx1255_info, &Reference::default()) {
+            Err(Sx1255Error::Lock(_)) => {},
+            other => panic!("expected lock failure, got {:?}", other),
+        }
+        let mode = Mode::decode(bus.regs[REG_MODE as usize]);
+        assert!(mode.tx_enable && !mode.driver_enable);
+
+        bus.regs[REG_STAT as usize] |= 0b0001;
+        sx1255_info.tx_freq = 436000000;
+        set_info(&mut bus, sx1255_info, &Reference::default()).unwrap();
+        assert!(Mode::decode(bus.regs[REG_MODE as usize]).driver_enable);
+    }
+
+    #[test]
+    fn plan_writes_mode_last() {
+        let mut current = keyed_regs();
+        current[REG_MODE as usize] = 0x01;
+        let mut desired = keyed_regs();
+        desired[REG_FRFM_RX as usize] ^= 1;
+        // keying from idle leaves the PA driver for after the Tx PLL locks
+        let relocking = Mode { driver_enable: false, tx_enable: true, rx_enable: true, ref_enable: true };
+        assert_eq!(plan_writes(&current, &desired), vec![
+            (REG_FRFH_RX, desired[REG_FRFH_RX as usize..REG_FRFH_RX as usize + 3].to_vec()),
+            (REG_MODE, vec![relocking.encode()]),
+        ]);
+    }
+}
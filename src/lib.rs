@@ -0,0 +1,279 @@
+//! Driver for the Semtech SX1255 on the M17 SX1255 HAT
+//!
+//! The chip is configured over SPI and reset with a GPIO line. [`Sx1255`]
+//! wraps a [`RegisterBus`] and provides the common operations, while the
+//! [`info`] and [`regs`] modules expose the register level primitives it is
+//! built on.
+//! [`MockBus`] models the register file in memory so code using the driver
+//! can be tested without the HAT.
+//!
+//! ```no_run
+//! use sx1255::{Sx1255, SPI_DEV};
+//!
+//! # fn main() -> Result<(), sx1255::Sx1255Error> {
+//! let mut sx1255 = Sx1255::open(SPI_DEV)?;
+//! sx1255.tune_rx(435000000)?;
+//! sx1255.enable_rx(true)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use spidev::{Spidev, SpidevOptions, SpiModeFlags};
+use gpio_cdev::{Chip, LineRequestFlags};
+
+use crate::band::{TxBand, AMATEUR_70CM, check_tx};
+use crate::freq::Reference;
+use crate::info::{SX1255Info, get_info, set_info, sx1255_readburst, sx1255_writeburst};
+use crate::regs::{NUM_REGS, Registers};
+use crate::status::{Status, wait_lock, wait_tx_lock};
+
+pub use crate::bus::{RegisterBus, MockBus};
+pub use crate::error::Sx1255Error;
+pub use crate::hw::HwConfig;
+
+pub mod band;
+pub mod bus;
+pub mod channel;
+pub mod duplex;
+pub mod error;
+pub mod fields;
+pub mod freq;
+pub mod info;
+pub mod file;
+pub mod hw;
+pub mod opts;
+pub mod profile;
+pub mod rate;
+pub mod regs;
+pub mod report;
+pub mod status;
+
+// defaults for the HAT on a Raspberry Pi, see HwConfig to change them
+
+/// SPI device the HAT is wired to on a Raspberry Pi
+pub static SPI_DEV: &str = "/dev/spidev0.0";
+/// SPI settings the SX1255 expects (CPOL = 0, CPHA = 0, MSB first)
+pub static SPI_OPTS: SpidevOptions = SpidevOptions {
+    bits_per_word: Some(8),
+    max_speed_hz: Some(500000),
+    lsb_first: Some(false),
+    spi_mode: Some(SpiModeFlags::SPI_MODE_0),
+};
+
+/// GPIO chip the reset line is on
+pub static GPIO_CHIP: &str = "/dev/gpiochip0";
+/// GPIO line connected to the SX1255 reset pin
+pub static RESET_LINE: u32 = 25;
+/// Frequency of the HAT's reference crystal in Hz
+pub static XTAL_FREQ: u32 = 32000000;
+/// Bands the PA driver may be enabled on, the HAT is built for 70 cm
+pub static TX_BANDS: [TxBand; 1] = [AMATEUR_70CM];
+
+/// Per-user settings, `$XDG_CONFIG_HOME/sx1255` or `~/.config/sx1255`
+pub fn user_config_dir() -> Option<PathBuf> {
+    let config = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config.join("sx1255"))
+}
+
+/// Pulses the reset line of the SX1255
+pub fn reset(hw: &HwConfig) -> Result<(), Sx1255Error> {
+    let (active, inactive) = if hw.reset_active_low { (0, 1) } else { (1, 0) };
+    let mut chip = Chip::new(&hw.gpio_chip)?;
+    let output = chip.get_line(hw.reset_line)?;
+    let output_handle = output.request(LineRequestFlags::OUTPUT, inactive, "sx1255")?;
+    output_handle.set_value(active)?;
+    output_handle.set_value(inactive)?;
+    Ok(())
+}
+
+/// An SX1255 connected via a [`RegisterBus`], normally SPI
+pub struct Sx1255<B: RegisterBus = Spidev> {
+    bus: B,
+    reference: Reference,
+    tx_bands: Vec<TxBand>,
+    tx_interlock: bool,
+}
+
+impl Sx1255<Spidev> {
+    /// Opens and configures the SPI device at `path`
+    pub fn open(path: &str) -> Result<Sx1255, Sx1255Error> {
+        let mut spi = Spidev::open(path)?;
+        spi.configure(&SPI_OPTS)?;
+        Ok(Sx1255::new(spi))
+    }
+
+    /// Opens and configures the SPI device described by a hardware profile
+    pub fn open_hw(hw: &HwConfig) -> Result<Sx1255, Sx1255Error> {
+        let mut spi = Spidev::open(&hw.spi_dev)?;
+        spi.configure(&hw.spi_options())?;
+        Ok(Sx1255::new(spi).with_reference(hw.reference()).with_tx_bands(hw.tx_bands.clone()))
+    }
+}
+
+impl<B: RegisterBus> Sx1255<B> {
+    /// Uses an already configured bus, such as a [`MockBus`]
+    pub fn new(bus: B) -> Sx1255<B> {
+        Sx1255 { bus, reference: Reference::default(), tx_bands: TX_BANDS.to_vec(), tx_interlock: true }
+    }
+
+    /// Uses a reference crystal other than the HAT's 32 MHz one
+    pub fn with_reference(mut self, reference: Reference) -> Sx1255<B> {
+        self.reference = reference;
+        self
+    }
+
+    /// Only allows the PA driver on inside `tx_bands` instead of [`TX_BANDS`]
+    pub fn with_tx_bands(mut self, tx_bands: Vec<TxBand>) -> Sx1255<B> {
+        self.tx_bands = tx_bands;
+        self
+    }
+
+    /// Turns the Tx band interlock off, or back on
+    ///
+    /// With it off the PA driver can be enabled on any frequency, make sure
+    /// you are allowed to transmit there.
+    pub fn set_tx_interlock(&mut self, enabled: bool) {
+        self.tx_interlock = enabled;
+    }
+
+    /// Bands the PA driver may be enabled on
+    pub fn tx_bands(&self) -> &[TxBand] {
+        &self.tx_bands
+    }
+
+    /// Checks `sx1255_info` only has the PA driver on inside the allowed Tx
+    /// bands, unless the interlock is off
+    pub fn check_tx(&self, sx1255_info: &SX1255Info) -> Result<(), Sx1255Error> {
+        if !self.tx_interlock {
+            return Ok(());
+        }
+        check_tx(&self.tx_bands, sx1255_info, &self.reference)
+    }
+
+    /// The reference clock used to convert frequencies
+    pub fn reference(&self) -> Reference {
+        self.reference
+    }
+
+    /// Reads consecutive registers starting at `addr`
+    pub fn read_regs(&mut self, addr: u8, vals: &mut [u8]) -> Result<(), Sx1255Error> {
+        if vals.is_empty() || addr as usize + vals.len() > NUM_REGS {
+            return Err(Sx1255Error::InvalidValue(format!("can't read {} registers from 0x{:02X}", vals.len(), addr)));
+        }
+        sx1255_readburst(&mut self.bus, addr, vals)?;
+        Ok(())
+    }
+
+    /// Writes consecutive registers starting at `addr`
+    ///
+    /// Raw writes go through the Tx band interlock too, nothing is written
+    /// if the PA driver would end up on out of band.
+    pub fn write_regs(&mut self, addr: u8, vals: &[u8]) -> Result<(), Sx1255Error> {
+        if addr as usize + vals.len() > NUM_REGS {
+            return Err(Sx1255Error::InvalidValue(format!("can't write {} registers from 0x{:02X}", vals.len(), addr)));
+        }
+        let mut regs = [0_u8; NUM_REGS];
+        sx1255_readburst(&mut self.bus, 0, &mut regs)?;
+        regs[addr as usize..addr as usize + vals.len()].copy_from_slice(vals);
+        self.check_tx(&SX1255Info::from_registers(&Registers::from_bytes(&regs), &self.reference))?;
+        sx1255_writeburst(&mut self.bus, addr, vals)?;
+        Ok(())
+    }
+
+    /// Reads the current state of the device
+    pub fn read_state(&mut self) -> Result<SX1255Info, Sx1255Error> {
+        let mut sx1255_info = SX1255Info::default();
+        get_info(&mut self.bus, &mut sx1255_info, &self.reference)?;
+        Ok(sx1255_info)
+    }
+
+    /// Reads the lock, XOSC, EOL and IISM status flags
+    pub fn read_status(&mut self) -> Result<Status, Sx1255Error> {
+        Status::read(&mut self.bus)
+    }
+
+    /// Waits up to `timeout` for the PLLs of the enabled Rx and Tx paths to lock
+    pub fn wait_lock(&mut self, timeout: Duration) -> Result<(), Sx1255Error> {
+        wait_lock(&mut self.bus, timeout)
+    }
+
+    /// Writes `sx1255_info` to the device
+    ///
+    /// Returns [`Sx1255Error::Interlock`] without writing anything if the PA
+    /// driver would be on outside the allowed Tx bands.
+    pub fn apply_state(&mut self, sx1255_info: SX1255Info) -> Result<(), Sx1255Error> {
+        self.check_tx(&sx1255_info)?;
+        set_info(&mut self.bus, sx1255_info, &self.reference)
+    }
+
+    /// Sets the Rx carrier frequency in Hz
+    pub fn tune_rx(&mut self, freq: u32) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.rx_freq = freq;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Sets the Tx carrier frequency in Hz
+    pub fn tune_tx(&mut self, freq: u32) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.tx_freq = freq;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Enables or disables the Rx part of the front-end
+    pub fn enable_rx(&mut self, enable: bool) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.rx_enable = enable;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Enables or disables the Tx part of the front-end (except the PA)
+    pub fn enable_tx(&mut self, enable: bool) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.tx_enable = enable;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Enables or disables the PA driver
+    pub fn enable_driver(&mut self, enable: bool) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.driver_enable = enable;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Keys the transmitter
+    ///
+    /// Tx is enabled first and the PA driver only once the Tx PLL has locked,
+    /// so nothing is radiated off frequency. If it doesn't lock within
+    /// `lock_timeout` Tx is switched back off and the error returned.
+    pub fn key_tx(&mut self, lock_timeout: Duration) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        // nothing is enabled if the driver would be refused at the end
+        self.check_tx(&SX1255Info { driver_enable: true, ..sx1255_info })?;
+        sx1255_info.tx_enable = true;
+        self.apply_state(sx1255_info)?;
+        // only Tx matters here, an enabled but unlocked Rx doesn't stop keying
+        if let Err(e) = wait_tx_lock(&mut self.bus, lock_timeout) {
+            // the lock failure is what the caller needs to hear about
+            let _ = self.unkey_tx();
+            return Err(e);
+        }
+        sx1255_info.driver_enable = true;
+        self.apply_state(sx1255_info)
+    }
+
+    /// Unkeys the transmitter, the PA driver goes off before Tx
+    pub fn unkey_tx(&mut self) -> Result<(), Sx1255Error> {
+        let mut sx1255_info = self.read_state()?;
+        sx1255_info.driver_enable = false;
+        self.apply_state(sx1255_info)?;
+        sx1255_info.tx_enable = false;
+        self.apply_state(sx1255_info)
+    }
+}
@@ -1,3 +1,4 @@
+/// Human readable descriptions of each register field value, indexed by value
 pub struct SettingOptions<'a> {
     pub tx_dac_gain:       [&'a str; 8],
     pub tx_mixer_gain:     [&'a str; 16],
@@ -25,6 +26,7 @@ pub struct SettingOptions<'a> {
     pub iism_status_flag:  [&'a str; 2],
 }
 
+/// Descriptions for every enumerated register field
 pub const OPTS: SettingOptions<'static> = SettingOptions {
     tx_dac_gain: [
         "maximum gain - 9 dB",
@@ -52,7 +54,7 @@ pub const OPTS: SettingOptions<'static> = SettingOptions {
     tx_pll_bw: ["75 KHz", "150 KHz", "225 KHz", "300 KHz",],
     tx_filter_bw: [
         "0.418 Mhz", "0.429 Mhz", "0.440 Mhz", "0.451 Mhz", "0.464 Mhz",
-        "0.476 Mhz", "0.490 Mhz", "0.504 Mhz", "0.520 Mhz", "0.546 Mhz",
+        "0.476 Mhz", "0.490 Mhz", "0.504 Mhz", "0.520 Mhz", "0.536 Mhz",
         "0.553 Mhz", "0.572 Mhz", "0.591 Mhz", "0.613 Mhz", "0.635 Mhz",
         "0.660 Mhz",
     ],
@@ -0,0 +1,135 @@
+use std::fs::{create_dir_all, read_dir};
+use std::path::PathBuf;
+
+use crate::error::Sx1255Error;
+use crate::user_config_dir;
+
+/// Profiles shared by every user of the system
+pub static SYSTEM_PROFILE_DIR: &str = "/etc/sx1255/profiles";
+
+/// Per-user profiles, `$XDG_CONFIG_HOME/sx1255/profiles` or
+/// `~/.config/sx1255/profiles`
+pub fn user_profile_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("profiles"))
+}
+
+/// A saved configuration file in a [`ProfileStore`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Named configuration files kept in a list of directories
+///
+/// Profiles are looked up in order, so one in an earlier directory hides one
+/// with the same name further down. New profiles are saved in the first.
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    dirs: Vec<PathBuf>,
+}
+
+impl Default for ProfileStore {
+    /// The user's profiles, then the system wide ones
+    fn default() -> ProfileStore {
+        let mut dirs: Vec<PathBuf> = user_profile_dir().into_iter().collect();
+        dirs.push(PathBuf::from(SYSTEM_PROFILE_DIR));
+        ProfileStore::new(dirs)
+    }
+}
+
+impl ProfileStore {
+    /// Uses the profiles in `dirs`
+    pub fn new(dirs: Vec<PathBuf>) -> ProfileStore {
+        ProfileStore { dirs }
+    }
+
+    /// Directories searched for profiles, in order
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Every profile by name, directories that don't exist are skipped
+    pub fn list(&self) -> Result<Vec<Profile>, Sx1255Error> {
+        let mut profiles: Vec<Profile> = Vec::new();
+        for dir in self.dirs.iter().filter(|dir| dir.is_dir()) {
+            let entries = read_dir(dir)
+                .map_err(|e| Sx1255Error::Config(format!("{}: {}", dir.display(), e)))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = match path.file_stem().and_then(|s| s.to_str()) {
+                    Some(name) if path.extension().is_some_and(|ext| ext == "toml") => name.to_string(),
+                    _ => continue,
+                };
+                if check_name(&name).is_ok() && !profiles.iter().any(|p| p.name == name) {
+                    profiles.push(Profile { name, path });
+                }
+            }
+        }
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Finds the file of profile `name`
+    pub fn find(&self, name: &str) -> Result<Profile, Sx1255Error> {
+        check_name(name)?;
+        self.dirs.iter()
+            .map(|dir| dir.join(format!("{}.toml", name)))
+            .find(|path| path.is_file())
+            .map(|path| Profile { name: name.to_string(), path })
+            .ok_or_else(|| Sx1255Error::Config(format!("no profile named {}", name)))
+    }
+
+    /// Where profile `name` is saved, creating the directory if needed
+    pub fn save_path(&self, name: &str) -> Result<PathBuf, Sx1255Error> {
+        check_name(name)?;
+        let dir = self.dirs.first()
+            .ok_or_else(|| Sx1255Error::Config("no profile directory to save to".to_string()))?;
+        create_dir_all(dir).map_err(|e| Sx1255Error::Config(format!("{}: {}", dir.display(), e)))?;
+        Ok(dir.join(format!("{}.toml", name)))
+    }
+}
+
+// names become file names, so keep them to something safe
+fn check_name(name: &str) -> Result<(), Sx1255Error> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if !valid {
+        return Err(Sx1255Error::Config(format!(
+            "invalid profile name {}, use letters, digits, '-', '_' and '.'", name)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::{remove_dir_all, write};
+
+    #[test]
+    fn earlier_dirs_win() {
+        let root = env::temp_dir().join(format!("sx1255-profiles-{}", std::process::id()));
+        let (user, system) = (root.join("user"), root.join("system"));
+        let store = ProfileStore::new(vec![user.clone(), system.clone()]);
+        assert!(store.list().unwrap().is_empty());
+
+        let path = store.save_path("m17").unwrap();
+        assert_eq!(path, user.join("m17.toml"));
+        write(&path, "").unwrap();
+        create_dir_all(&system).unwrap();
+        write(system.join("m17.toml"), "").unwrap();
+        write(system.join("test.toml"), "").unwrap();
+        write(system.join("notes.txt"), "").unwrap();
+
+        let names: Vec<_> = store.list().unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["m17", "test"]);
+        assert_eq!(store.find("m17").unwrap().path, user.join("m17.toml"));
+        assert_eq!(store.find("test").unwrap().path, system.join("test.toml"));
+        assert!(store.find("repeater").is_err());
+        assert!(store.find("../m17").is_err());
+        assert!(store.save_path("").is_err());
+        remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::cmp::Reverse;
+
+use crate::freq::{Reference, CLK_DIV_RATIOS};
+use crate::info::{SX1255Info, VALID_R_VALUES};
+use crate::regs::IismMode;
+
+/// Sample widths `sx1255-pub` can stream, preferred when planning
+const PCM_BITS: [u32; 2] = [16, 32];
+
+/// IISM clocks and the I/Q sample rate for one configuration
+///
+/// The bit clock is CLK_OUT. In mode B2 each word clock period carries an I
+/// and a Q sample, in mode B1 they take one word clock half each, so the
+/// word clock runs at the sample rate or half of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rates {
+    pub mode: IismMode,
+    pub clk_div: u8,
+    pub r: u32,
+    /// Bit clock (CLK_OUT) in Hz, missing if the divider code isn't used
+    pub bit_clock: Option<f64>,
+    /// Word clock in Hz, only defined in modes B1 and B2
+    pub word_clock: Option<f64>,
+    /// Bit clock periods per I or Q sample, only defined in modes B1 and B2
+    pub bits_per_sample: Option<f64>,
+    /// I/Q sample rate in Hz
+    pub sample_rate: f64,
+}
+
+impl Rates {
+    /// Works out the rates for a mode, divider code and decimation factor
+    pub fn new(reference: &Reference, mode: IismMode, clk_div: u8, r: u32) -> Rates {
+        let xtal = reference.actual_xtal();
+        let div = CLK_DIV_RATIOS.get(clk_div as usize).map(|div| *div as f64);
+        let (word_clock, bits_per_sample) = match (mode, div) {
+            (IismMode::B1, Some(div)) => (Some(xtal / (2 * r) as f64), Some(r as f64 / div)),
+            (IismMode::B2, Some(div)) => (Some(xtal / r as f64), Some(r as f64 / (2.0 * div))),
+            _ => (None, None),
+        };
+        Rates {
+            mode,
+            clk_div,
+            r,
+            bit_clock: reference.clk_out(clk_div),
+            word_clock,
+            bits_per_sample,
+            sample_rate: reference.sample_rate(r),
+        }
+    }
+
+    /// Rates for the IISM settings in `sx1255_info`
+    pub fn of(sx1255_info: &SX1255Info, reference: &Reference) -> Rates {
+        Rates::new(reference, sx1255_info.iism_mode, sx1255_info.iism_clk_div, sx1255_info.r)
+    }
+
+    /// Whether the IISM can frame samples this way, a whole number of
+    /// 8 to 32 bits per sample
+    pub fn valid(&self) -> bool {
+        self.bits_per_sample.is_some_and(|bits| bits.fract() == 0.0 && (8.0..=32.0).contains(&bits))
+    }
+
+    /// Copies the settings into `sx1255_info`
+    pub fn apply(&self, sx1255_info: &mut SX1255Info) {
+        sx1255_info.iism_mode = self.mode;
+        sx1255_info.iism_clk_div = self.clk_div;
+        sx1255_info.r = self.r;
+    }
+}
+
+/// Finds the valid B1 or B2 configuration closest to `target` Hz
+///
+/// Ties go to 16 or 32 bits per sample, then to staying in B1 if that's
+/// `mode` and B2 otherwise, then to the slowest bit clock.
+pub fn plan(reference: &Reference, target: f64, mode: IismMode) -> Option<Rates> {
+    let mode = if mode == IismMode::B1 { IismMode::B1 } else { IismMode::B2 };
+    let mut candidates = Vec::new();
+    for candidate_mode in [IismMode::B1, IismMode::B2] {
+        for clk_div in 0..CLK_DIV_RATIOS.len() as u8 {
+            for r in VALID_R_VALUES {
+                let rates = Rates::new(reference, candidate_mode, clk_div, r);
+                if rates.valid() {
+                    candidates.push(rates);
+                }
+            }
+        }
+    }
+    let rank = |rates: &Rates| {
+        let pcm = rates.bits_per_sample.is_some_and(|bits| PCM_BITS.contains(&(bits as u32)));
+        ((rates.sample_rate - target).abs(), (!pcm, rates.mode != mode, Reverse(rates.clk_div)))
+    };
+    candidates.into_iter().min_by(|a, b| {
+        let (a, b) = (rank(a), rank(b));
+        a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn works_out_clocks() {
+        let reference = Reference::default();
+        let rates = Rates::new(&reference, IismMode::B2, 2, 128);
+        assert_eq!(rates.bit_clock, Some(8000000.0));
+        assert_eq!(rates.word_clock, Some(250000.0));
+        assert_eq!(rates.bits_per_sample, Some(16.0));
+        assert_eq!(rates.sample_rate, 250000.0);
+        assert!(rates.valid());
+
+        let rates = Rates::new(&reference, IismMode::B1, 2, 128);
+        assert_eq!(rates.word_clock, Some(125000.0));
+        assert_eq!(rates.bits_per_sample, Some(32.0));
+        assert!(!Rates::new(&reference, IismMode::B2, 0, 128).valid());
+        assert!(!Rates::new(&reference, IismMode::B2, 4, 27).valid());
+        assert!(!Rates::new(&reference, IismMode::A, 2, 128).valid());
+    }
+
+    #[test]
+    fn plans_nearest_rate() {
+        let reference = Reference::default();
+        let rates = plan(&reference, 250000.0, IismMode::B2).unwrap();
+        assert_eq!((rates.mode, rates.r, rates.bits_per_sample), (IismMode::B2, 128, Some(16.0)));
+
+        // 32 MHz / 96 kHz is 333.3, the closest factors are 288 and 384
+        let rates = plan(&reference, 96000.0, IismMode::B2).unwrap();
+        assert!(rates.valid());
+        assert_eq!((rates.r, rates.clk_div), (384, 4));
+        assert!(VALID_R_VALUES.iter().all(|r| (reference.sample_rate(*r) - 96000.0).abs()
+            >= (rates.sample_rate - 96000.0).abs()));
+
+        let b1 = plan(&reference, 250000.0, IismMode::B1).unwrap();
+        assert_eq!(b1.mode, IismMode::B1);
+    }
+}
@@ -0,0 +1,685 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Sx1255Error;
+
+// register addresses, see section 5 of the datasheet
+pub const REG_MODE: u8       = 0x00;
+pub const REG_FRFH_RX: u8    = 0x01;
+pub const REG_FRFM_RX: u8    = 0x02;
+pub const REG_FRFL_RX: u8    = 0x03;
+pub const REG_FRFH_TX: u8    = 0x04;
+pub const REG_FRFM_TX: u8    = 0x05;
+pub const REG_FRFL_TX: u8    = 0x06;
+pub const REG_VERSION: u8    = 0x07;
+pub const REG_TXFE1: u8      = 0x08;
+pub const REG_TXFE2: u8      = 0x09;
+pub const REG_TXFE3: u8      = 0x0A;
+pub const REG_TXFE4: u8      = 0x0B;
+pub const REG_RXFE1: u8      = 0x0C;
+pub const REG_RXFE2: u8      = 0x0D;
+pub const REG_RXFE3: u8      = 0x0E;
+pub const REG_IO_MAP: u8     = 0x0F;
+pub const REG_CK_SEL: u8     = 0x10;
+pub const REG_STAT: u8       = 0x11;
+pub const REG_IISM: u8       = 0x12;
+pub const REG_DIG_BRIDGE: u8 = 0x13;
+
+/// Number of registers in the SX1255 register file (0x00-0x13)
+pub const NUM_REGS: usize = 0x14;
+
+/// Datasheet names of the registers, indexed by address
+pub static REG_NAMES: [&str; NUM_REGS] = [
+    "RegMode",
+    "RegFrfhRx", "RegFrfmRx", "RegFrflRx",
+    "RegFrfhTx", "RegFrfmTx", "RegFrflTx",
+    "RegVersion",
+    "RegTxFe1", "RegTxFe2", "RegTxFe3", "RegTxFe4",
+    "RegRxFe1", "RegRxFe2", "RegRxFe3",
+    "RegIoMap",
+    "RegCkSel",
+    "RegStat",
+    "RegIism",
+    "RegDigBridge",
+];
+
+/// A single 8-bit register made up of bitfields
+pub trait Register: Sized {
+    /// Address of the register
+    const ADDR: u8;
+    /// Bits covered by the fields of the register
+    const MASK: u8;
+
+    /// Decodes the register contents, ignoring unused bits
+    fn decode(byte: u8) -> Self;
+    /// Encodes the fields, leaving unused bits cleared
+    fn encode(&self) -> u8;
+}
+
+fn field(byte: u8, shift: u8, width: u8) -> u8 {
+    (byte >> shift) & ((1 << width) - 1)
+}
+
+fn bit(byte: u8, shift: u8) -> bool {
+    field(byte, shift, 1) != 0
+}
+
+// declares an enum for a bitfield, every value the field can hold must have
+// a variant so decoding is total and round-trips exactly
+macro_rules! field_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident = $value:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+        #[serde(try_from = "u8", into = "u8")]
+        #[repr(u8)]
+        pub enum $name {
+            $($variant = $value),+
+        }
+
+        impl $name {
+            fn from_bits(bits: u8) -> $name {
+                match bits {
+                    $($value => $name::$variant,)+
+                    _ => unreachable!("field value out of range"),
+                }
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = Sx1255Error;
+
+            fn try_from(value: u8) -> Result<$name, Sx1255Error> {
+                match value {
+                    $($value => Ok($name::$variant),)+
+                    _ => Err(Sx1255Error::InvalidValue(
+                        format!("{} is not a valid {} value", value, stringify!($name)))),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> u8 {
+                value as u8
+            }
+        }
+
+        // the register code, so files and reports keep showing numbers
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", *self as u8)
+            }
+        }
+    };
+}
+
+field_enum! {
+    /// Tx DAC gain in 3 dB steps, values 4-7 are test modes
+    DacGain {
+        Minus9 = 0, Minus6 = 1, Minus3 = 2, Max = 3,
+        TestMinus9 = 4, TestMinus6 = 5, TestMinus3 = 6, TestMax = 7,
+    }
+}
+
+field_enum! {
+    /// Resistance in parallel with the Tx mixer tank
+    TankRes {
+        R950 = 0, R1110 = 1, R1320 = 2, R1650 = 3, R2180 = 4, R3240 = 5,
+        R6000 = 6, Open = 7,
+    }
+}
+
+field_enum! {
+    /// Rx or Tx PLL bandwidth
+    PllBw { Bw75k = 0, Bw150k = 1, Bw225k = 2, Bw300k = 3 }
+}
+
+field_enum! {
+    /// Rx LNA gain relative to the highest gain
+    LnaGain {
+        Unused0 = 0, G1 = 1, G2 = 2, G3 = 3, G4 = 4, G5 = 5, G6 = 6, Unused7 = 7,
+    }
+}
+
+field_enum! {
+    /// Rx input impedance
+    RxZin { Ohm50 = 0, Ohm200 = 1 }
+}
+
+field_enum! {
+    /// Rx analog roofing filter bandwidth
+    PgaBw { Bw1500k = 0, Bw1000k = 1, Bw750k = 2, Bw500k = 3 }
+}
+
+field_enum! {
+    /// Clock output on pad CLK_OUT
+    ClkOut { Disabled = 0, Enabled = 1 }
+}
+
+field_enum! {
+    /// Clock source for the Tx DAC
+    DacClock { Internal = 0, External = 1 }
+}
+
+field_enum! {
+    /// End of life (low battery) indicator
+    Eol { Normal = 0, BatteryLow = 1 }
+}
+
+field_enum! {
+    /// IISM interface mode
+    IismMode { A = 0, B1 = 1, B2 = 2, Unused = 3 }
+}
+
+field_enum! {
+    /// IISM truncation mode in Rx and Tx
+    Truncation { Msb = 0, Lsb = 1 }
+}
+
+/// RegMode (0x00)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mode {
+    pub driver_enable: bool,
+    pub tx_enable: bool,
+    pub rx_enable: bool,
+    pub ref_enable: bool,
+}
+
+impl Register for Mode {
+    const ADDR: u8 = REG_MODE;
+    const MASK: u8 = 0b00001111;
+
+    fn decode(byte: u8) -> Mode {
+        Mode {
+            driver_enable: bit(byte, 3),
+            tx_enable:     bit(byte, 2),
+            rx_enable:     bit(byte, 1),
+            ref_enable:    bit(byte, 0),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.driver_enable as u8) << 3 |
+        (self.tx_enable as u8)     << 2 |
+        (self.rx_enable as u8)     << 1 |
+        (self.ref_enable as u8)
+    }
+}
+
+/// RegTxFe1 (0x08)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFe1 {
+    pub dac_gain: DacGain,
+    pub mixer_gain: u8,
+}
+
+impl Register for TxFe1 {
+    const ADDR: u8 = REG_TXFE1;
+    const MASK: u8 = 0b01111111;
+
+    fn decode(byte: u8) -> TxFe1 {
+        TxFe1 {
+            dac_gain:   DacGain::from_bits(field(byte, 4, 3)),
+            mixer_gain: field(byte, 0, 4),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.dac_gain as u8) << 4 |
+        (self.mixer_gain & 0b1111)
+    }
+}
+
+/// RegTxFe2 (0x09)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFe2 {
+    pub mixer_tank_cap: u8,
+    pub mixer_tank_res: TankRes,
+}
+
+impl Register for TxFe2 {
+    const ADDR: u8 = REG_TXFE2;
+    const MASK: u8 = 0b00111111;
+
+    fn decode(byte: u8) -> TxFe2 {
+        TxFe2 {
+            mixer_tank_cap: field(byte, 3, 3),
+            mixer_tank_res: TankRes::from_bits(field(byte, 0, 3)),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.mixer_tank_cap & 0b111) << 3 |
+        (self.mixer_tank_res as u8)
+    }
+}
+
+/// RegTxFe3 (0x0A)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFe3 {
+    pub pll_bw: PllBw,
+    pub filter_bw: u8,
+}
+
+impl Register for TxFe3 {
+    const ADDR: u8 = REG_TXFE3;
+    const MASK: u8 = 0b01101111;
+
+    fn decode(byte: u8) -> TxFe3 {
+        TxFe3 {
+            pll_bw:    PllBw::from_bits(field(byte, 5, 2)),
+            filter_bw: field(byte, 0, 4),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.pll_bw as u8) << 5 |
+        (self.filter_bw & 0b1111)
+    }
+}
+
+/// RegTxFe4 (0x0B)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFe4 {
+    pub dac_bw: u8,
+}
+
+impl Register for TxFe4 {
+    const ADDR: u8 = REG_TXFE4;
+    const MASK: u8 = 0b00000111;
+
+    fn decode(byte: u8) -> TxFe4 {
+        TxFe4 { dac_bw: field(byte, 0, 3) }
+    }
+
+    fn encode(&self) -> u8 {
+        self.dac_bw & 0b111
+    }
+}
+
+/// RegRxFe1 (0x0C)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxFe1 {
+    pub lna_gain: LnaGain,
+    pub pga_gain: u8,
+    pub zin: RxZin,
+}
+
+impl Register for RxFe1 {
+    const ADDR: u8 = REG_RXFE1;
+    const MASK: u8 = 0b11111111;
+
+    fn decode(byte: u8) -> RxFe1 {
+        RxFe1 {
+            lna_gain: LnaGain::from_bits(field(byte, 5, 3)),
+            pga_gain: field(byte, 1, 4),
+            zin:      RxZin::from_bits(field(byte, 0, 1)),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.lna_gain as u8)        << 5 |
+        (self.pga_gain & 0b1111)     << 1 |
+        (self.zin as u8)
+    }
+}
+
+/// RegRxFe2 (0x0D)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxFe2 {
+    pub adc_bw: u8,
+    pub adc_trim: u8,
+    pub pga_bw: PgaBw,
+}
+
+impl Register for RxFe2 {
+    const ADDR: u8 = REG_RXFE2;
+    const MASK: u8 = 0b11111111;
+
+    fn decode(byte: u8) -> RxFe2 {
+        RxFe2 {
+            adc_bw:   field(byte, 5, 3),
+            adc_trim: field(byte, 2, 3),
+            pga_bw:   PgaBw::from_bits(field(byte, 0, 2)),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.adc_bw & 0b111)   << 5 |
+        (self.adc_trim & 0b111) << 2 |
+        (self.pga_bw as u8)
+    }
+}
+
+/// RegRxFe3 (0x0E)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RxFe3 {
+    pub pll_bw: PllBw,
+    pub adc_temp: bool,
+}
+
+impl Register for RxFe3 {
+    const ADDR: u8 = REG_RXFE3;
+    const MASK: u8 = 0b00000111;
+
+    fn decode(byte: u8) -> RxFe3 {
+        RxFe3 {
+            pll_bw:   PllBw::from_bits(field(byte, 1, 2)),
+            adc_temp: bit(byte, 0),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.pll_bw as u8) << 1 |
+        (self.adc_temp as u8)
+    }
+}
+
+/// RegIoMap (0x0F)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoMap {
+    pub map0: u8,
+    pub map1: u8,
+    pub map2: u8,
+    pub map3: u8,
+}
+
+impl Register for IoMap {
+    const ADDR: u8 = REG_IO_MAP;
+    const MASK: u8 = 0b11111111;
+
+    fn decode(byte: u8) -> IoMap {
+        IoMap {
+            map0: field(byte, 6, 2),
+            map1: field(byte, 4, 2),
+            map2: field(byte, 2, 2),
+            map3: field(byte, 0, 2),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.map0 & 0b11) << 6 |
+        (self.map1 & 0b11) << 4 |
+        (self.map2 & 0b11) << 2 |
+        (self.map3 & 0b11)
+    }
+}
+
+/// RegCkSel (0x10)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CkSel {
+    pub dig_loopback_en: bool,
+    pub rf_loopback_en: bool,
+    pub ckout_enable: ClkOut,
+    pub ck_select_tx_dac: DacClock,
+}
+
+impl Register for CkSel {
+    const ADDR: u8 = REG_CK_SEL;
+    const MASK: u8 = 0b00001111;
+
+    fn decode(byte: u8) -> CkSel {
+        CkSel {
+            dig_loopback_en:  bit(byte, 3),
+            rf_loopback_en:   bit(byte, 2),
+            ckout_enable:     ClkOut::from_bits(field(byte, 1, 1)),
+            ck_select_tx_dac: DacClock::from_bits(field(byte, 0, 1)),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.dig_loopback_en as u8)  << 3 |
+        (self.rf_loopback_en as u8)   << 2 |
+        (self.ckout_enable as u8)     << 1 |
+        (self.ck_select_tx_dac as u8)
+    }
+}
+
+/// RegStat (0x11), read only
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stat {
+    pub eol: Eol,
+    pub xosc_ready: bool,
+    pub pll_lock_rx: bool,
+    pub pll_lock_tx: bool,
+}
+
+impl Register for Stat {
+    const ADDR: u8 = REG_STAT;
+    const MASK: u8 = 0b00001111;
+
+    fn decode(byte: u8) -> Stat {
+        Stat {
+            eol:         Eol::from_bits(field(byte, 3, 1)),
+            xosc_ready:  bit(byte, 2),
+            pll_lock_rx: bit(byte, 1),
+            pll_lock_tx: bit(byte, 0),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.eol as u8)         << 3 |
+        (self.xosc_ready as u8)  << 2 |
+        (self.pll_lock_rx as u8) << 1 |
+        (self.pll_lock_tx as u8)
+    }
+}
+
+/// RegIism (0x12)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Iism {
+    pub rx_disable: bool,
+    pub tx_disable: bool,
+    pub mode: IismMode,
+    pub clk_div: u8,
+}
+
+impl Register for Iism {
+    const ADDR: u8 = REG_IISM;
+    const MASK: u8 = 0b11111111;
+
+    fn decode(byte: u8) -> Iism {
+        Iism {
+            rx_disable: bit(byte, 7),
+            tx_disable: bit(byte, 6),
+            mode:       IismMode::from_bits(field(byte, 4, 2)),
+            clk_div:    field(byte, 0, 4),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        (self.rx_disable as u8) << 7 |
+        (self.tx_disable as u8) << 6 |
+        (self.mode as u8)       << 4 |
+        (self.clk_div & 0b1111)
+    }
+}
+
+/// RegDigBridge (0x13)
+///
+/// The decimation/interpolation factor is `mant * 3^m * 2^n`, see
+/// [`calc_r`](crate::info::calc_r).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigBridge {
+    /// 8 for the 1st set of factors, 9 for the 2nd
+    pub mant: u8,
+    pub m: u8,
+    pub n: u8,
+    pub truncation: Truncation,
+    pub status_flag: bool,
+}
+
+impl Register for DigBridge {
+    const ADDR: u8 = REG_DIG_BRIDGE;
+    const MASK: u8 = 0b11111110;
+
+    fn decode(byte: u8) -> DigBridge {
+        DigBridge {
+            mant:        if bit(byte, 7) { 9 } else { 8 },
+            m:           field(byte, 6, 1),
+            n:           field(byte, 3, 3),
+            truncation:  Truncation::from_bits(field(byte, 2, 1)),
+            status_flag: bit(byte, 1),
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        ((self.mant == 9) as u8)   << 7 |
+        (self.m & 0b1)             << 6 |
+        (self.n & 0b111)           << 3 |
+        (self.truncation as u8)    << 2 |
+        (self.status_flag as u8)   << 1
+    }
+}
+
+/// The complete register file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub mode: Mode,
+    /// Rx synthesizer word (RegFrfRx, 0x01-0x03)
+    pub frf_rx: u32,
+    /// Tx synthesizer word (RegFrfTx, 0x04-0x06)
+    pub frf_tx: u32,
+    pub version: u8,
+    pub txfe1: TxFe1,
+    pub txfe2: TxFe2,
+    pub txfe3: TxFe3,
+    pub txfe4: TxFe4,
+    pub rxfe1: RxFe1,
+    pub rxfe2: RxFe2,
+    pub rxfe3: RxFe3,
+    pub iomap: IoMap,
+    pub ck_sel: CkSel,
+    pub stat: Stat,
+    pub iism: Iism,
+    pub dig_bridge: DigBridge,
+}
+
+fn frf_from_bytes(high: u8, middle: u8, low: u8) -> u32 {
+    (high as u32) << 16 | (middle as u32) << 8 | (low as u32)
+}
+
+impl Registers {
+    /// Decodes a dump of registers 0x00-0x13
+    pub fn from_bytes(regs: &[u8; NUM_REGS]) -> Registers {
+        Registers {
+            mode:       Mode::decode(regs[REG_MODE as usize]),
+            frf_rx:     frf_from_bytes(regs[REG_FRFH_RX as usize], regs[REG_FRFM_RX as usize], regs[REG_FRFL_RX as usize]),
+            frf_tx:     frf_from_bytes(regs[REG_FRFH_TX as usize], regs[REG_FRFM_TX as usize], regs[REG_FRFL_TX as usize]),
+            version:    regs[REG_VERSION as usize],
+            txfe1:      TxFe1::decode(regs[REG_TXFE1 as usize]),
+            txfe2:      TxFe2::decode(regs[REG_TXFE2 as usize]),
+            txfe3:      TxFe3::decode(regs[REG_TXFE3 as usize]),
+            txfe4:      TxFe4::decode(regs[REG_TXFE4 as usize]),
+            rxfe1:      RxFe1::decode(regs[REG_RXFE1 as usize]),
+            rxfe2:      RxFe2::decode(regs[REG_RXFE2 as usize]),
+            rxfe3:      RxFe3::decode(regs[REG_RXFE3 as usize]),
+            iomap:      IoMap::decode(regs[REG_IO_MAP as usize]),
+            ck_sel:     CkSel::decode(regs[REG_CK_SEL as usize]),
+            stat:       Stat::decode(regs[REG_STAT as usize]),
+            iism:       Iism::decode(regs[REG_IISM as usize]),
+            dig_bridge: DigBridge::decode(regs[REG_DIG_BRIDGE as usize]),
+        }
+    }
+
+    /// Encodes registers 0x00-0x13
+    pub fn to_bytes(&self) -> [u8; NUM_REGS] {
+        let mut regs = [0_u8; NUM_REGS];
+        regs[REG_MODE as usize]       = self.mode.encode();
+        regs[REG_FRFH_RX as usize]    = (self.frf_rx >> 16) as u8;
+        regs[REG_FRFM_RX as usize]    = (self.frf_rx >> 8) as u8;
+        regs[REG_FRFL_RX as usize]    = self.frf_rx as u8;
+        regs[REG_FRFH_TX as usize]    = (self.frf_tx >> 16) as u8;
+        regs[REG_FRFM_TX as usize]    = (self.frf_tx >> 8) as u8;
+        regs[REG_FRFL_TX as usize]    = self.frf_tx as u8;
+        regs[REG_VERSION as usize]    = self.version;
+        regs[REG_TXFE1 as usize]      = self.txfe1.encode();
+        regs[REG_TXFE2 as usize]      = self.txfe2.encode();
+        regs[REG_TXFE3 as usize]      = self.txfe3.encode();
+        regs[REG_TXFE4 as usize]      = self.txfe4.encode();
+        regs[REG_RXFE1 as usize]      = self.rxfe1.encode();
+        regs[REG_RXFE2 as usize]      = self.rxfe2.encode();
+        regs[REG_RXFE3 as usize]      = self.rxfe3.encode();
+        regs[REG_IO_MAP as usize]     = self.iomap.encode();
+        regs[REG_CK_SEL as usize]     = self.ck_sel.encode();
+        regs[REG_STAT as usize]       = self.stat.encode();
+        regs[REG_IISM as usize]       = self.iism.encode();
+        regs[REG_DIG_BRIDGE as usize] = self.dig_bridge.encode();
+        regs
+    }
+}
+
+/// Bits of each register that are modelled by [`Registers`]
+pub static REG_MASKS: [u8; NUM_REGS] = [
+    Mode::MASK,
+    0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF,
+    0xFF,
+    TxFe1::MASK, TxFe2::MASK, TxFe3::MASK, TxFe4::MASK,
+    RxFe1::MASK, RxFe2::MASK, RxFe3::MASK,
+    IoMap::MASK,
+    CkSel::MASK,
+    Stat::MASK,
+    Iism::MASK,
+    DigBridge::MASK,
+];
+
+/// Bits of each register that can be written, VERSION, STAT and the IISM
+/// status flag are read only
+pub static WRITABLE_MASKS: [u8; NUM_REGS] = [
+    Mode::MASK,
+    0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF,
+    0x00,
+    TxFe1::MASK, TxFe2::MASK, TxFe3::MASK, TxFe4::MASK,
+    RxFe1::MASK, RxFe2::MASK, RxFe3::MASK,
+    IoMap::MASK,
+    CkSel::MASK,
+    0x00,
+    Iism::MASK,
+    DigBridge::MASK & !0b00000010,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trips<R: Register>() {
+        for byte in 0..=255_u8 {
+            assert_eq!(R::decode(byte).encode(), byte & R::MASK, "register 0x{:02X}", R::ADDR);
+        }
+    }
+
+    #[test]
+    fn registers_round_trip() {
+        round_trips::<Mode>();
+        round_trips::<TxFe1>();
+        round_trips::<TxFe2>();
+        round_trips::<TxFe3>();
+        round_trips::<TxFe4>();
+        round_trips::<RxFe1>();
+        round_trips::<RxFe2>();
+        round_trips::<RxFe3>();
+        round_trips::<IoMap>();
+        round_trips::<CkSel>();
+        round_trips::<Stat>();
+        round_trips::<Iism>();
+        round_trips::<DigBridge>();
+    }
+
+    #[test]
+    fn register_file_round_trips() {
+        let mut regs = [0_u8; NUM_REGS];
+        for (i, reg) in regs.iter_mut().enumerate() {
+            *reg = 0xA5_u8.rotate_left(i as u32) & REG_MASKS[i];
+        }
+        assert_eq!(Registers::from_bytes(&regs).to_bytes(), regs);
+    }
+
+    #[test]
+    fn enums_reject_out_of_range() {
+        assert_eq!(LnaGain::try_from(1).unwrap(), LnaGain::G1);
+        assert_eq!(IismMode::try_from(1).unwrap(), IismMode::B1);
+        assert!(PgaBw::try_from(4).is_err());
+        assert!(RxZin::try_from(2).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+
+use crate::fields::FIELDS;
+use crate::freq::Reference;
+use crate::info::SX1255Info;
+
+/// Version of the report layout, bumped if a key is ever renamed or removed
+pub const REPORT_SCHEMA: u32 = 1;
+
+/// Everything `info` shows, for serializing to JSON, TOML or YAML
+///
+/// `state` holds the raw value of every field (the same keys as saved
+/// files, plus the read only VERSION and STAT bits) and `decoded` holds the
+/// same fields as the human readable text of the `info` report.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub schema: u32,
+    pub reference: ReferenceReport,
+    pub state: SX1255Info,
+    pub decoded: BTreeMap<&'static str, String>,
+}
+
+/// Reference clock settings and the rates derived from them
+#[derive(Debug, Serialize)]
+pub struct ReferenceReport {
+    /// Nominal crystal frequency in Hz
+    pub xtal_freq: u32,
+    /// Crystal correction in ppm
+    pub ppm: f64,
+    /// Carrier frequency correction in Hz
+    pub offset: i32,
+    /// PLL frequency step in Hz
+    pub step: f64,
+    /// I/Q sample rate in Hz
+    pub sample_rate: f64,
+    /// CLK_OUT frequency in Hz, missing if the divider code isn't used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clk_out: Option<f64>,
+}
+
+impl Report {
+    /// Builds a report of `sx1255_info`
+    pub fn new(sx1255_info: &SX1255Info, reference: &Reference) -> Report {
+        Report {
+            schema: REPORT_SCHEMA,
+            reference: ReferenceReport {
+                xtal_freq:   reference.xtal_freq,
+                ppm:         reference.ppm,
+                offset:      reference.offset,
+                step:        reference.step(),
+                sample_rate: reference.sample_rate(sx1255_info.r),
+                clk_out:     reference.clk_out(sx1255_info.iism_clk_div),
+            },
+            state: *sx1255_info,
+            decoded: FIELDS.iter()
+                .map(|field| (field.name, field.describe((field.get)(sx1255_info))))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+
+use crate::bus::RegisterBus;
+use crate::error::Sx1255Error;
+use crate::info::sx1255_readreg;
+use crate::regs::{DigBridge, Eol, Mode, Register, Stat};
+
+// how often wait_lock checks the lock flags
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The status flags that change while the chip is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Status {
+    pub eol: bool,
+    pub xosc_ready: bool,
+    pub pll_lock_rx: bool,
+    pub pll_lock_tx: bool,
+    pub iism_status_flag: bool,
+}
+
+/// A status flag changing, or its state when watching starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Event {
+    /// Short name to match hooks against, e.g. `tx_unlock`
+    pub name: &'static str,
+    /// Field the flag is reported as by `info`
+    pub field: &'static str,
+    /// New state of the flag
+    pub value: bool,
+    /// Human readable description, e.g. `Tx PLL unlocked`
+    pub message: &'static str,
+}
+
+// field name, then (event name, message) for the flag set and cleared
+type Flag = (&'static str, (&'static str, &'static str), (&'static str, &'static str));
+
+static FLAGS: [Flag; 5] = [
+    ("xosc_ready",       ("xosc_ready", "XOSC ready"),          ("xosc_lost", "XOSC not ready")),
+    ("pll_lock_rx",      ("rx_lock", "Rx PLL locked"),          ("rx_unlock", "Rx PLL unlocked")),
+    ("pll_lock_tx",      ("tx_lock", "Tx PLL locked"),          ("tx_unlock", "Tx PLL unlocked")),
+    ("eol",              ("eol", "battery low (VBAT < EOL)"),   ("eol_clear", "battery ok (VBAT > EOL)")),
+    ("iism_status_flag", ("iism_error", "IISM error, IISM off"), ("iism_ok", "IISM ok")),
+];
+
+/// Every event name, for validating hooks
+pub fn event_names() -> impl Iterator<Item = &'static str> {
+    FLAGS.iter().flat_map(|(_, on, off)| [on.0, off.0])
+}
+
+impl Status {
+    /// Reads the STAT and DIG_BRIDGE registers
+    pub fn read<B: RegisterBus>(bus: &mut B) -> Result<Status, Sx1255Error> {
+        let stat = Stat::decode(sx1255_readreg(bus, Stat::ADDR)?);
+        let dig_bridge = DigBridge::decode(sx1255_readreg(bus, DigBridge::ADDR)?);
+        Ok(Status {
+            eol:              stat.eol == Eol::BatteryLow,
+            xosc_ready:       stat.xosc_ready,
+            pll_lock_rx:      stat.pll_lock_rx,
+            pll_lock_tx:      stat.pll_lock_tx,
+            iism_status_flag: dig_bridge.status_flag,
+        })
+    }
+
+    fn flag(&self, field: &str) -> bool {
+        match field {
+            "eol" => self.eol,
+            "xosc_ready" => self.xosc_ready,
+            "pll_lock_rx" => self.pll_lock_rx,
+            "pll_lock_tx" => self.pll_lock_tx,
+            "iism_status_flag" => self.iism_status_flag,
+            _ => unreachable!("unknown status flag {}", field),
+        }
+    }
+
+    /// Events for the flags that changed since `previous`, or for every
+    /// flag when there is nothing to compare with
+    pub fn events(&self, previous: Option<&Status>) -> Vec<Event> {
+        FLAGS.iter()
+            .filter(|(field, _, _)| previous.is_none_or(|p| p.flag(field) != self.flag(field)))
+            .map(|&(field, on, off)| {
+                let value = self.flag(field);
+                let (name, message) = if value { on } else { off };
+                Event { name, field, value, message }
+            })
+            .collect()
+    }
+}
+
+/// Waits for the PLLs of the enabled Rx and Tx paths to lock
+///
+/// Returns [`Sx1255Error::Lock`] naming the unlocked PLLs if they haven't
+/// locked after `timeout`. Nothing is waited for when both paths are off.
+pub fn wait_lock<B: RegisterBus>(bus: &mut B, timeout: Duration) -> Result<(), Sx1255Error> {
+    let start = Instant::now();
+    loop {
+        let mode = Mode::decode(sx1255_readreg(bus, Mode::ADDR)?);
+        let status = Status::read(bus)?;
+        let unlocked: Vec<&str> = [
+            ("Rx", mode.rx_enable && !status.pll_lock_rx),
+            ("Tx", mode.tx_enable && !status.pll_lock_tx),
+        ].iter().filter(|(_, unlocked)| *unlocked).map(|(name, _)| *name).collect();
+        if unlocked.is_empty() {
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            return Err(Sx1255Error::Lock(format!("{} PLL not locked after {} ms",
+                unlocked.join(" and "), timeout.as_millis())));
+        }
+        sleep(LOCK_POLL_INTERVAL);
+    }
+}
+
+/// Waits for the Tx PLL to lock, whatever the Rx path is doing
+pub fn wait_tx_lock<B: RegisterBus>(bus: &mut B, timeout: Duration) -> Result<(), Sx1255Error> {
+    let start = Instant::now();
+    while !Status::read(bus)?.pll_lock_tx {
+        if start.elapsed() >= timeout {
+            return Err(Sx1255Error::Lock(format!("Tx PLL not locked after {} ms", timeout.as_millis())));
+        }
+        sleep(LOCK_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::MockBus;
+    use crate::regs::{REG_MODE, REG_STAT};
+
+    #[test]
+    fn reports_transitions() {
+        let mut bus = MockBus::new();
+        let first = Status::read(&mut bus).unwrap();
+        assert!(first.xosc_ready && !first.pll_lock_tx);
+        assert_eq!(first.events(None).len(), 5);
+        assert!(first.events(Some(&first)).is_empty());
+
+        bus.regs[REG_STAT as usize] = 0b1101;
+        let second = Status::read(&mut bus).unwrap();
+        let names: Vec<_> = second.events(Some(&first)).iter().map(|e| e.name).collect();
+        assert_eq!(names, ["tx_lock", "eol"]);
+
+        bus.regs[REG_STAT as usize] = 0b0100;
+        let events = Status::read(&mut bus).unwrap().events(Some(&second));
+        assert_eq!(events[0].message, "Tx PLL unlocked");
+        assert!(!events[0].value);
+        assert!(event_names().any(|n| n == "iism_error"));
+    }
+
+    #[test]
+    fn waits_for_enabled_plls() {
+        let mut bus = MockBus::new();
+        let timeout = Duration::from_millis(5);
+        // nothing enabled, nothing to wait for
+        wait_lock(&mut bus, timeout).unwrap();
+
+        bus.regs[REG_MODE as usize] = 0b0111;
+        let e = wait_lock(&mut bus, timeout).unwrap_err();
+        assert_eq!(e.to_string(), "PLL lock failed: Rx and Tx PLL not locked after 5 ms");
+
+        bus.regs[REG_STAT as usize] = 0b0110;
+        let e = wait_lock(&mut bus, timeout).unwrap_err();
+        assert_eq!(e.to_string(), "PLL lock failed: Tx PLL not locked after 5 ms");
+        bus.regs[REG_MODE as usize] = 0b0011;
+        wait_lock(&mut bus, timeout).unwrap();
+    }
+}